@@ -0,0 +1,43 @@
+use chrono::{Datelike, NaiveDate};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use jpn_holidays_wasm::compute_holidays_raw;
+use jpn_holidays_wasm::datebook::calendar::{is_holiday, Holiday, HolidaySet};
+use jpn_holidays_wasm::datebook::render::{format_holidays, OutputFormat};
+use jpn_holidays_wasm::datebook::timebase::{all_years_with_equinox_data, get_equinox_dates};
+
+fn formats(c: &mut Criterion) {
+    c.bench_function("holiday json 2024", |b| b.iter(|| format_holidays(&compute_holidays_raw(black_box(2024)).unwrap(), OutputFormat::Json)));
+    c.bench_function("holiday csv 2024", |b| b.iter(|| format_holidays(&compute_holidays_raw(black_box(2024)).unwrap(), OutputFormat::Csv)));
+    c.bench_function("holiday yaml 2024", |b| b.iter(|| format_holidays(&compute_holidays_raw(black_box(2024)).unwrap(), OutputFormat::Yaml)));
+}
+
+// The first is_holiday call of a year builds its HolidaySet (cold); later calls hit the per-year cache (warm).
+fn lookups(c: &mut Criterion) {
+    let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    c.bench_function("is_holiday 2024-01-01 first call (cold)", |b| b.iter(|| HolidaySet::new(black_box(2024)).unwrap().contains(black_box(date))));
+    is_holiday(date).unwrap();
+    c.bench_function("is_holiday 2024-01-01 cached (warm)", |b| b.iter(|| is_holiday(black_box(date))));
+}
+
+// HolidaySet membership costs the same with the statutory holidays as with every day a holiday.
+fn holiday_set(c: &mut Criterion) {
+    let date = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+    let statutory = HolidaySet::new(2024).unwrap();
+    let every_day = HolidaySet::from_holidays(2024, date.with_ordinal(1).unwrap().iter_days()
+        .take(366)
+        .map(|date| Holiday { name: "休業日".to_string(), date, ..Holiday::default() })
+        .collect());
+    c.bench_function("HolidaySet contains, statutory", |b| b.iter(|| statutory.contains(black_box(date))));
+    c.bench_function("HolidaySet contains, every day", |b| b.iter(|| every_day.contains(black_box(date))));
+    c.bench_function("HolidaySet get, every day", |b| b.iter(|| every_day.get(black_box(date))));
+}
+
+// Parsing the equinox table on every call (cold) against the OnceLock-cached table (warm).
+fn equinox_cache(c: &mut Criterion) {
+    c.bench_function("equinox table parse (cold)", |b| b.iter(get_equinox_dates));
+    all_years_with_equinox_data();
+    c.bench_function("equinox years cached (warm)", |b| b.iter(all_years_with_equinox_data));
+}
+
+criterion_group!(benches, formats, lookups, holiday_set, equinox_cache);
+criterion_main!(benches);
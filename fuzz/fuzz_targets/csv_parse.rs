@@ -0,0 +1,11 @@
+#![no_main]
+
+use jpn_holidays_wasm::datebook::timebase::{parse_equinox_dates, parse_schedule, parse_special_holidays};
+use libfuzzer_sys::fuzz_target;
+
+// Malformed input must produce an error, never a panic.
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_schedule(data);
+    let _ = parse_equinox_dates(data);
+    let _ = parse_special_holidays(data);
+});
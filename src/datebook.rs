@@ -1,2 +1,13 @@
 pub mod timebase;
 pub mod calendar;
+pub mod render;
+pub mod util;
+pub mod era;
+pub mod workday;
+pub mod observance;
+pub mod sekki;
+pub mod rokuyo;
+pub mod eto;
+pub mod zassetsu;
+pub mod translation;
+mod astro;
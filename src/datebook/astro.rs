@@ -0,0 +1,106 @@
+//! # Astro
+//! Low-precision positions of the sun (Meeus, *Astronomical Algorithms*, ch. 25) and
+//! new moons (ch. 49) for the calendar modules. Times are Julian days; ΔT is ignored,
+//! so results are within a few minutes of the published instants for years around 2000.
+//!
+use chrono::{Duration, NaiveDate};
+
+// Julian day of 1970-01-01 00:00 UT
+const UNIX_EPOCH_JD: f64 = 2440587.5;
+const TROPICAL_YEAR: f64 = 365.242189;
+
+/// Julian day of 00:00 UT on a date.
+pub(crate) fn julian_day(date: NaiveDate) -> f64 {
+    let days = date.signed_duration_since(NaiveDate::default()).num_days();
+    UNIX_EPOCH_JD + days as f64
+}
+
+/// Date in Japan Standard Time (UT+9) of a Julian day.
+pub(crate) fn jst_date(jd: f64) -> NaiveDate {
+    let days = (jd - UNIX_EPOCH_JD + 9.0 / 24.0).floor() as i64;
+    NaiveDate::default() + Duration::days(days)
+}
+
+/// Apparent ecliptic longitude of the sun in degrees, in `[0, 360)`.
+pub(crate) fn sun_longitude(jd: f64) -> f64 {
+    let t = (jd - 2451545.0) / 36525.0;
+    let l0 = 280.46646 + 36000.76983 * t + 0.0003032 * t * t;
+    let m = (357.52911 + 35999.05029 * t - 0.0001537 * t * t).to_radians();
+    let c = (1.914602 - 0.004817 * t - 0.000014 * t * t) * m.sin()
+        + (0.019993 - 0.000101 * t) * (2.0 * m).sin()
+        + 0.000289 * (3.0 * m).sin();
+    let omega = (125.04 - 1934.136 * t).to_radians();
+    (l0 + c - 0.00569 - 0.00478 * omega.sin()).rem_euclid(360.0)
+}
+
+/// Julian day at which the sun reaches `longitude`, searching from `estimate`.
+///
+/// `estimate` should be within a few weeks of the answer.
+pub(crate) fn sun_reaches(longitude: f64, estimate: f64) -> f64 {
+    let mut jd = estimate;
+    for _ in 0..10 {
+        let delta = (longitude - sun_longitude(jd) + 180.0).rem_euclid(360.0) - 180.0;
+        if delta.abs() < 1e-7 {
+            break;
+        }
+        jd += delta * TROPICAL_YEAR / 360.0;
+    }
+    jd
+}
+
+// Julian day of the first new moon of 2000 (k = 0) and the mean synodic month
+const NEW_MOON_EPOCH: f64 = 2451550.09766;
+const SYNODIC_MONTH: f64 = 29.530588861;
+
+/// Index of the last new moon before `jd`, for use with `new_moon`.
+pub(crate) fn lunation_before(jd: f64) -> i64 {
+    let mut k = ((jd - NEW_MOON_EPOCH) / SYNODIC_MONTH).floor() as i64;
+    while new_moon(k) >= jd {
+        k -= 1;
+    }
+    while new_moon(k + 1) < jd {
+        k += 1;
+    }
+    k
+}
+
+/// Julian day of the `k`-th new moon after January 2000 (Meeus ch. 49, without the
+/// planetary terms).
+pub(crate) fn new_moon(k: i64) -> f64 {
+    let k = k as f64;
+    let t = k / 1236.85;
+    let (t2, t3, t4) = (t * t, t * t * t, t * t * t * t);
+    let mean = NEW_MOON_EPOCH + SYNODIC_MONTH * k + 0.00015437 * t2 - 0.00000015 * t3 + 0.00000000073 * t4;
+    let e = 1.0 - 0.002516 * t - 0.0000074 * t2;
+    let m = (2.5534 + 29.10535670 * k - 0.0000014 * t2 - 0.00000011 * t3).to_radians();
+    let mp = (201.5643 + 385.81693528 * k + 0.0107582 * t2 + 0.00001238 * t3 - 0.000000058 * t4).to_radians();
+    let f = (160.7108 + 390.67050284 * k - 0.0016118 * t2 - 0.00000227 * t3 + 0.000000011 * t4).to_radians();
+    let omega = (124.7746 - 1.56375588 * k + 0.0020672 * t2 + 0.00000215 * t3).to_radians();
+
+    let correction = -0.40720 * mp.sin()
+        + 0.17241 * e * m.sin()
+        + 0.01608 * (2.0 * mp).sin()
+        + 0.01039 * (2.0 * f).sin()
+        + 0.00739 * e * (mp - m).sin()
+        - 0.00514 * e * (mp + m).sin()
+        + 0.00208 * e * e * (2.0 * m).sin()
+        - 0.00111 * (mp - 2.0 * f).sin()
+        - 0.00057 * (mp + 2.0 * f).sin()
+        + 0.00056 * e * (2.0 * mp + m).sin()
+        - 0.00042 * (3.0 * mp).sin()
+        + 0.00042 * e * (m + 2.0 * f).sin()
+        + 0.00038 * e * (m - 2.0 * f).sin()
+        - 0.00024 * e * (2.0 * mp - m).sin()
+        - 0.00017 * omega.sin()
+        - 0.00007 * (mp + 2.0 * m).sin()
+        + 0.00004 * (2.0 * mp - 2.0 * f).sin()
+        + 0.00004 * (3.0 * m).sin()
+        + 0.00003 * (mp + m - 2.0 * f).sin()
+        + 0.00003 * (2.0 * mp + 2.0 * f).sin()
+        - 0.00003 * (mp + m + 2.0 * f).sin()
+        + 0.00003 * (mp - m + 2.0 * f).sin()
+        - 0.00002 * (mp - m - 2.0 * f).sin()
+        - 0.00002 * (3.0 * mp + m).sin()
+        + 0.00002 * (4.0 * mp).sin();
+    mean + correction
+}
@@ -1,41 +1,1161 @@
 //! # Calendar
 //! This module provides a function to get a list of japanese holidays in a year.
 //! 
-use chrono::{Datelike, Duration, Weekday, NaiveDate, Local, DateTime};
-use chrono::TimeZone;
-use anyhow::{Result, Error, Ok};
-use serde::Serialize;
-use super::timebase::{get_schedule, get_equinox_dates, Condition};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Months, Weekday, NaiveDate, Utc};
+use anyhow::{anyhow, Context, Result, Error, Ok};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use super::timebase::{VERNAL_EQUINOX_NAME, all_years_with_equinox_data, get_schedule_for_year_with_warnings, equinox_table, get_special_holidays_with_warnings, strict, BaseHolyday, Condition, ParseWarning};
+use super::render::{format_holidays, OutputFormat};
+use super::observance::{observances, ObservanceSet};
+use super::era::{era_of, wareki, EraName};
+use super::eto::year_eto;
+use super::workday::{BusinessCalendar, WeekendDays};
+use super::util::{parse_holiday_date, get_month_num_from_string, nth_weekday_of_month, NthWeek, get_weekday_from_string, weekday_kanji};
 
 /// Holiday
-#[derive(Serialize)]
+///
+/// `Holiday::default()` is an unnamed, non-substitute holiday on 1970-01-01.
+///
+/// Holidays are ordered by date, then by name.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
 pub struct Holiday {
     pub name: String, // name of holiday
     pub date: NaiveDate, // date of holiday
     pub substitute: bool, // if it is a substitute holiday
+    #[serde(default, skip_serializing_if = "HolidayType::is_national")]
+    pub kind: HolidayType, // only serialized for custom holidays and observances
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub substitute_for: Option<NaiveDate>, // date of the holiday a substitute holiday stands in for
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String, // stable key such as "sports-day", "substitute:sports-day" or "custom:<name>", empty for observances
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>, // background such as the law that added the holiday, from `base.csv`
+}
+
+/// Source of a holiday.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum HolidayType {
+    #[default]
+    National, // statutory, including substitute holidays and 国民の休日
+    Custom, // added through `CustomHolidays`
+    Observance, // not a day off, see `observance`
+}
+
+impl HolidayType {
+    fn is_national(&self) -> bool {
+        *self == HolidayType::National
+    }
+
+    /// Serialized name ("national", "custom", "observance").
+    pub fn key(&self) -> &'static str {
+        match self {
+            HolidayType::National => "national",
+            HolidayType::Custom => "custom",
+            HolidayType::Observance => "observance",
+        }
+    }
+
+    /// Japanese label (祝日, 休業日, 行事).
+    pub fn kanji(&self) -> &'static str {
+        match self {
+            HolidayType::National => "祝日",
+            HolidayType::Custom => "休業日",
+            HolidayType::Observance => "行事",
+        }
+    }
+}
+
+impl Holiday {
+    /// ISO 8601 week number of the date (1-53).
+    pub fn week_of_year(&self) -> u32 {
+        self.date.iso_week().week()
+    }
+
+    /// Year the ISO week belongs to, which differs from the calendar year
+    /// around New Year (2024-12-30 is in week 1 of 2025).
+    pub fn week_year(&self) -> i32 {
+        self.date.iso_week().year()
+    }
+
+    /// Date formatted with a `NaiveDate::format` string such as `"%Y/%m/%d"`.
+    ///
+    /// Like `NaiveDate::format`, panics when `fmt` is invalid; check user-supplied
+    /// formats with `RenderOptions::validate` first.
+    pub fn date_formatted(&self, fmt: &str) -> String {
+        self.date.format(fmt).to_string()
+    }
+
+    /// Date in the Japanese calendar, e.g. `令和6年1月1日` (see `era::wareki`).
+    pub fn date_japanese(&self) -> String {
+        wareki(self.date)
+    }
+
+    /// Era and era year of the date, e.g. `(Reiwa, 6)` for 2024 (see `era::era_of`).
+    ///
+    /// 2019-04-30 is `(Heisei, 31)` and 2019-05-01 is `(Reiwa, 1)`.
+    pub fn era_year(&self) -> Option<(EraName, u32)> {
+        era_of(self.date)
+    }
+}
+
+impl Ord for Holiday {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.date.cmp(&other.date)
+            .then_with(|| self.name.cmp(&other.name))
+            .then_with(|| self.substitute.cmp(&other.substitute))
+            .then_with(|| self.kind.cmp(&other.kind))
+            .then_with(|| self.substitute_for.cmp(&other.substitute_for))
+            .then_with(|| self.id.cmp(&other.id))
+            .then_with(|| self.note.cmp(&other.note))
+    }
+}
+
+/// Formats a holiday as `2024-05-06 (月) 振替休日(こどもの日)`.
+///
+/// Custom holidays and observances are marked with their kind: `2024-03-03 (日) ひな祭り [行事]`.
+impl fmt::Display for Holiday {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({}) {}", self.date, weekday_kanji(self.date.weekday()), self.name)?;
+        if !self.kind.is_national() {
+            write!(f, " [{}]", self.kind.kanji())?;
+        }
+        fmt::Result::Ok(())
+    }
+}
+
+impl PartialOrd for Holiday {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Builder for custom `Holiday` values.
+///
+/// `name` and `date` are required; `substitute` defaults to `false` and `kind` to `National`.
+#[derive(Debug, Clone, Default)]
+pub struct HolidayBuilder {
+    name: Option<String>,
+    date: Option<NaiveDate>,
+    substitute: Option<bool>,
+    kind: Option<HolidayType>,
+    substitute_for: Option<NaiveDate>,
+    id: Option<String>,
+    note: Option<String>,
+}
+
+impl HolidayBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name(mut self, n: &str) -> Self {
+        self.name = Some(n.to_string());
+        self
+    }
+
+    pub fn date(mut self, d: NaiveDate) -> Self {
+        self.date = Some(d);
+        self
+    }
+
+    pub fn substitute(mut self, s: bool) -> Self {
+        self.substitute = Some(s);
+        self
+    }
+
+    pub fn kind(mut self, k: HolidayType) -> Self {
+        self.kind = Some(k);
+        self
+    }
+
+    pub fn substitute_for(mut self, d: NaiveDate) -> Self {
+        self.substitute_for = Some(d);
+        self
+    }
+
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    pub fn note(mut self, n: &str) -> Self {
+        self.note = Some(n.to_string());
+        self
+    }
+
+    pub fn build(self) -> Result<Holiday, &'static str> {
+        std::result::Result::Ok(Holiday {
+            name: self.name.ok_or("name is required")?,
+            date: self.date.ok_or("date is required")?,
+            substitute: self.substitute.unwrap_or(false),
+            kind: self.kind.unwrap_or_default(),
+            substitute_for: self.substitute_for,
+            id: self.id.unwrap_or_default(),
+            note: self.note,
+        })
+    }
+}
+
+/// Name of a day sandwiched between two national holidays.
+pub const CITIZENS_HOLIDAY_NAME: &str = "国民の休日";
+
+/// `Holiday::id` of 国民の休日.
+pub const CITIZENS_HOLIDAY_ID: &str = "citizens-holiday";
+
+/// Holidays of a single year.
+#[derive(Debug, Clone)]
+pub struct HolidayCalendar {
+    pub year: u32,
+    holidays: Vec<Holiday>,
+    set: HolidaySet,
+    weekend: WeekendDays,
+    warnings: Vec<ParseWarning>,
+}
+
+impl HolidayCalendar {
+    /// Malformed data rows are skipped and reported by `warnings`.
+    pub fn new(year: u32) -> Result<Self, Error> {
+        let (holidays, warnings) = compute_holidays_with_warnings(year);
+        let set = HolidaySet::from_holidays(year, holidays.clone());
+        Ok(HolidayCalendar { year, holidays, set, weekend: WeekendDays::default(), warnings })
+    }
+
+    /// Statutory holidays merged with `custom` ones.
+    ///
+    /// A custom holiday on a statutory holiday is dropped.
+    pub fn with_custom(year: u32, custom: &CustomHolidays) -> Result<Self, Error> {
+        let (mut holidays, warnings) = compute_holidays_with_warnings(year);
+        let mut added: Vec<Holiday> = Vec::new();
+        for h in custom.for_year(year)? {
+            if !holidays.iter().chain(&added).any(|x| x.date == h.date) {
+                added.push(h);
+            }
+        }
+        holidays.append(&mut added);
+        holidays.sort();
+        if custom.substitutes {
+            let sundays: Vec<Holiday> = holidays.iter()
+                .filter(|h| h.kind == HolidayType::Custom && h.date.weekday() == Weekday::Sun)
+                .cloned()
+                .collect();
+            for h in sundays {
+                let mut date = h.date + Duration::days(1);
+                while holidays.iter().any(|x| x.date == date) {
+                    date += Duration::days(1);
+                }
+                holidays.push(Holiday {
+                    name: format!("振替休日({})", h.name),
+                    date,
+                    substitute: true,
+                    kind: HolidayType::Custom,
+                    substitute_for: Some(h.date),
+                    id: substitute_id(&h.id),
+                    note: None,
+                });
+            }
+            holidays.sort();
+        }
+        let set = HolidaySet::from_holidays(year, holidays.clone());
+        Ok(HolidayCalendar { year, holidays, set, weekend: WeekendDays::default(), warnings })
+    }
+
+    /// Use `weekend` instead of Saturday and Sunday for the business days.
+    pub fn with_weekend(mut self, weekend: WeekendDays) -> Self {
+        self.weekend = weekend;
+        self
+    }
+
+    /// Check whether `date` is one of the calendar's holidays.
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        if date.year() == self.year as i32 {
+            self.set.contains(date)
+        } else {
+            // a custom substitute for 12/31 falls in the next year
+            self.holidays.iter().any(|h| h.date == date)
+        }
+    }
+
+    /// Check whether `date` is neither a weekend nor one of the calendar's holidays.
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        !self.weekend.contains(date.weekday()) && !self.is_holiday(date)
+    }
+
+    /// Holidays sorted by date.
+    pub fn holidays(&self) -> &[Holiday] {
+        &self.holidays
+    }
+
+    /// Business days of `month`, in order.
+    ///
+    /// Unlike the `workday` helpers, this honors the custom holidays of the calendar.
+    pub fn business_days(&self, month: u32) -> Result<Vec<NaiveDate>, Error> {
+        let first = NaiveDate::from_ymd_opt(self.year as i32, month, 1)
+            .ok_or_else(|| anyhow!("invalid month {}", month))?;
+        Ok(first.iter_days()
+            .take_while(|d| d.month() == month)
+            .filter(|d| self.is_business_day(*d))
+            .collect())
+    }
+
+    /// Holidays named exactly `name`.
+    pub fn find(&self, name: &str) -> Vec<&Holiday> {
+        self.holidays.iter().filter(|h| h.name == name).collect()
+    }
+
+    /// Holidays whose name matches `name` case-insensitively, ignoring a parenthesized suffix,
+    /// so `振替休日` finds every substitute holiday.
+    pub fn find_normalized(&self, name: &str) -> Vec<&Holiday> {
+        let name = normalize_holiday_name(name).to_lowercase();
+        self.holidays.iter()
+            .filter(|h| normalize_holiday_name(&h.name).to_lowercase() == name)
+            .collect()
+    }
+
+    /// Data rows that were skipped while computing the holidays.
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+
+    /// Holidays of `month` laid out as weeks, each week running Monday to Sunday.
+    ///
+    /// A slot is `None` when the day is not a holiday or belongs to an adjacent month.
+    pub fn weeks(&self, month: u32) -> Result<Vec<[Option<&Holiday>; 7]>, Error> {
+        let first = NaiveDate::from_ymd_opt(self.year as i32, month, 1)
+            .ok_or_else(|| anyhow!("invalid month {}", month))?;
+        let mut day = first - Duration::days(first.weekday().num_days_from_monday() as i64);
+        let mut weeks = Vec::new();
+        while day.month() == month || day < first {
+            let mut week: [Option<&Holiday>; 7] = [None; 7];
+            for slot in week.iter_mut() {
+                if day.month() == month {
+                    *slot = self.holidays.iter().find(|h| h.date == day);
+                }
+                day += Duration::days(1);
+            }
+            weeks.push(week);
+        }
+        Ok(weeks)
+    }
+}
+
+// 振替休日(建国記念の日) -> 振替休日
+fn normalize_holiday_name(s: &str) -> &str {
+    let end = s.find(['(', '（']).unwrap_or(s.len());
+    s[..end].trim()
+}
+
+impl IntoIterator for HolidayCalendar {
+    type Item = Holiday;
+    type IntoIter = std::vec::IntoIter<Holiday>;
+
+    /// Holidays sorted by date.
+    fn into_iter(self) -> Self::IntoIter {
+        self.holidays.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a HolidayCalendar {
+    type Item = &'a Holiday;
+    type IntoIter = std::slice::Iter<'a, Holiday>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.holidays.iter()
+    }
+}
+
+/// Holidays of a single year with constant-time lookup by date, for loops over many days.
+///
+/// Dates are kept as a bitmap by day of the year; a holiday is found by its rank in the bitmap,
+/// so neither `contains` nor `get` allocates or scans. Holidays outside `year` are dropped,
+/// and of several holidays on one date only the first in order is kept.
+#[derive(Debug, Clone, Default)]
+pub struct HolidaySet {
+    pub year: u32,
+    bits: [u64; 6], // bit n is day n + 1 of the year
+    holidays: Vec<Holiday>, // one per set bit, in date order
+}
+
+impl HolidaySet {
+    /// Statutory holidays of `year`.
+    pub fn new(year: u32) -> Result<Self, Error> {
+        Ok(HolidaySet::from_holidays(year, compute_holidays(year)?))
+    }
+
+    /// A set of the given holidays that fall in `year`.
+    pub fn from_holidays(year: u32, mut holidays: Vec<Holiday>) -> Self {
+        holidays.retain(|h| h.date.year() == year as i32);
+        holidays.sort();
+        holidays.dedup_by_key(|h| h.date);
+        let mut bits = [0u64; 6];
+        for h in &holidays {
+            let day = h.date.ordinal0() as usize;
+            bits[day / 64] |= 1 << (day % 64);
+        }
+        HolidaySet { year, bits, holidays }
+    }
+
+    /// Check whether `date` is one of the holidays.
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        if date.year() != self.year as i32 {
+            return false;
+        }
+        let day = date.ordinal0() as usize;
+        self.bits[day / 64] & (1 << (day % 64)) != 0
+    }
+
+    /// Get the holiday on `date`, if any.
+    pub fn get(&self, date: NaiveDate) -> Option<HolidayRef<'_>> {
+        if !self.contains(date) {
+            return None;
+        }
+        let day = date.ordinal0() as usize;
+        let below: u32 = self.bits[..day / 64].iter().map(|w| w.count_ones()).sum();
+        let rank = below + (self.bits[day / 64] & ((1 << (day % 64)) - 1)).count_ones();
+        self.holidays.get(rank as usize).map(HolidayRef::from)
+    }
+
+    /// Holidays sorted by date.
+    pub fn holidays(&self) -> &[Holiday] {
+        &self.holidays
+    }
+
+    /// Number of holidays.
+    pub fn len(&self) -> usize {
+        self.holidays.len()
+    }
+
+    /// Check whether there are no holidays.
+    pub fn is_empty(&self) -> bool {
+        self.holidays.is_empty()
+    }
+}
+
+/// A holiday found in a `HolidaySet`, borrowing its strings instead of cloning them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HolidayRef<'a> {
+    pub date: NaiveDate,
+    pub name: &'a str,
+    pub id: &'a str,
+    pub kind: HolidayType,
+    pub substitute: bool,
+    holiday: &'a Holiday,
+}
+
+impl<'a> HolidayRef<'a> {
+    /// The whole holiday, including `substitute_for` and `note`.
+    pub fn holiday(&self) -> &'a Holiday {
+        self.holiday
+    }
+}
+
+impl<'a> From<&'a Holiday> for HolidayRef<'a> {
+    fn from(holiday: &'a Holiday) -> Self {
+        HolidayRef {
+            date: holiday.date,
+            name: &holiday.name,
+            id: &holiday.id,
+            kind: holiday.kind,
+            substitute: holiday.substitute,
+            holiday,
+        }
+    }
+}
+
+/// Non-statutory closures such as a founding day or 年末年始, merged by `HolidayCalendar::with_custom`.
+///
+/// ```
+/// use jpn_holidays_wasm::datebook::calendar::{CustomHolidays, HolidayCalendar};
+///
+/// let custom = CustomHolidays::new()
+///     .annual("年末年始", 12, 29)
+///     .annual("年末年始", 12, 30)
+///     .annual("年末年始", 12, 31);
+/// let calendar = HolidayCalendar::with_custom(2024, &custom).unwrap();
+/// assert_eq!(calendar.holidays().last().unwrap().name, "年末年始");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CustomHolidays {
+    rules: Vec<(String, CustomRule)>,
+    pub substitutes: bool, // give custom holidays on a Sunday a substitute holiday
+}
+
+#[derive(Debug, Clone)]
+enum CustomRule {
+    Fixed(NaiveDate),
+    Annual(u32, u32), // month, day
+    Relative(Condition),
+}
+
+impl CustomHolidays {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A one-off holiday on `date`.
+    pub fn fixed(mut self, name: &str, date: NaiveDate) -> Self {
+        self.rules.push((name.to_string(), CustomRule::Fixed(date)));
+        self
+    }
+
+    /// A holiday on the same month and day every year.
+    pub fn annual(mut self, name: &str, month: u32, day: u32) -> Self {
+        self.rules.push((name.to_string(), CustomRule::Annual(month, day)));
+        self
+    }
+
+    /// A holiday on the n-th weekday of a month every year, as in base.csv.
+    pub fn relative(mut self, name: &str, condition: Condition) -> Self {
+        self.rules.push((name.to_string(), CustomRule::Relative(condition)));
+        self
+    }
+
+    pub fn substitutes(mut self, s: bool) -> Self {
+        self.substitutes = s;
+        self
+    }
+
+    fn for_year(&self, year: u32) -> Result<Vec<Holiday>, Error> {
+        let mut days = Vec::new();
+        for (name, rule) in &self.rules {
+            let date = match rule {
+                CustomRule::Fixed(date) if date.year() == year as i32 => *date,
+                CustomRule::Fixed(_) => continue,
+                CustomRule::Annual(month, day) => match NaiveDate::from_ymd_opt(year as i32, *month, *day) {
+                    Some(date) => date,
+                    None if *month == 2 && *day == 29 => continue,
+                    None => return Err(anyhow!("custom holiday '{}': invalid date {}/{}", name, month, day)),
+                },
+                CustomRule::Relative(condition) => get_relative_date(year, condition.clone())
+                    .with_context(|| format!("custom holiday '{}': invalid condition", name))?,
+            };
+            days.push(Holiday {
+                name: name.clone(),
+                date,
+                substitute: false,
+                kind: HolidayType::Custom,
+                substitute_for: None,
+                id: custom_id(name),
+                note: None,
+            });
+        }
+        Ok(days)
+    }
+}
+
+/// Get a list of japanese holidays in a year, serialized in `format`.
+#[deprecated(since = "0.2.0", note = "Use compute_holidays_raw() and render::format_holidays() instead")]
+pub fn holiday(format: OutputFormat, year: u32) -> Result<String, Error> {
+    format_holidays(&compute_holidays(year)?, format)
 }
 
 /// Get a list of japanese holidays in a year.
-pub fn holiday(year: u32)-> Result<Vec<Holiday>, Error> {
+///
+/// Fails for years without equinox data (see `timebase::all_years_with_equinox_data`), whose
+/// 春分の日 and 秋分の日 are unknown; `compute_holidays_with_warnings` returns the other holidays.
+pub fn compute_holidays(year: u32)-> Result<Vec<Holiday>, Error> {
+    strict(compute_holidays_with_warnings(year))
+}
+
+/// Like `compute_holidays`, skipping malformed data rows and returning a warning for each.
+pub fn compute_holidays_with_warnings(year: u32) -> (Vec<Holiday>, Vec<ParseWarning>) {
+    compute_holidays_from(year, get_schedule_for_year_with_warnings(year))
+}
+
+// `compute_holidays_with_warnings` over the given base.csv rows, so tests can feed malformed data.
+fn compute_holidays_from(year: u32, (schedule, mut warnings): (Vec<BaseHolyday>, Vec<ParseWarning>)) -> (Vec<Holiday>, Vec<ParseWarning>) {
     //List of holidays stipulated in the Holidays Act
-    let mut m = prepara(year);
-    let e= pick_exuinox_from_year(year);
+    let mut m = prepara(year, schedule, &mut warnings);
+    let e= pick_exuinox_from_year(year, &mut warnings);
     m.extend(e);
-    substitute_adjustment(&mut m);
+    citizens_holiday_adjustment(year, &mut m);
 
     //sort
-    m.sort_by(|a, b| a.date.cmp(&b.date));
+    m.sort_by_key(|h| h.date);
+
+    substitute_adjustment(&mut m);
+    m.sort_by_key(|h| h.date);
+
+    (m, warnings)
+}
+
+/// Extra and excluded holidays applied on top of the statutory ones.
+///
+/// `HolidayConfig::default()` changes nothing.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct HolidayConfig {
+    pub extra_holidays: Vec<(NaiveDate, String)>, // added as `HolidayType::Custom`
+    pub excluded_names: Vec<String>, // exact holiday names to drop
+    pub include_observances: bool, // add the 五節句 (`ObservanceSet::Sekku`) as observances
+}
 
-    Ok(m)
+/// Get the holidays of a year with `config` applied.
+///
+/// Exclusions are applied first; an extra holiday on a date that is still a holiday is dropped.
+/// Observances are added last and never replace a holiday.
+pub fn compute_holidays_with_config(year: u32, config: &HolidayConfig) -> Result<Vec<Holiday>, Error> {
+    let mut holidays: Vec<Holiday> = compute_holidays(year)?
+        .into_iter()
+        .filter(|h| !config.excluded_names.contains(&h.name))
+        .collect();
+    for (date, name) in &config.extra_holidays {
+        if date.year() != year as i32 || holidays.iter().any(|h| h.date == *date) {
+            continue;
+        }
+        holidays.push(Holiday {
+            name: name.clone(),
+            date: *date,
+            substitute: false,
+            kind: HolidayType::Custom,
+            substitute_for: None,
+            id: custom_id(name),
+            note: None,
+        });
+    }
+    if config.include_observances {
+        holidays.extend(observances(year, &ObservanceSet::Sekku)?);
+    }
+    holidays.sort();
+    Ok(holidays)
+}
+
+/// Get the holidays of a year without substitute holidays.
+pub fn holidays_excluding_substitutes(year: u32) -> Result<Vec<Holiday>, Error> {
+    Ok(compute_holidays(year)?.into_iter().filter(|h| !h.substitute).collect())
+}
+
+/// Get only the substitute holidays of a year.
+pub fn substitute_holidays_only(year: u32) -> Result<Vec<Holiday>, Error> {
+    Ok(compute_holidays(year)?.into_iter().filter(|h| h.substitute).collect())
+}
+
+/// Differences between the holidays of two years, matched by name.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub struct HolidayDiff {
+    pub only_in_a: Vec<Holiday>,
+    pub only_in_b: Vec<Holiday>,
+    pub date_changes: Vec<(Holiday, Holiday)>, // same name on a different month and day, as (a, b)
+}
+
+/// Compare the holidays of two years by name
+/// (from 2020 to 2021, 秋分の日 moves from 9/22 to 9/23).
+pub fn holidays_diff(year_a: u32, year_b: u32) -> Result<HolidayDiff, Error> {
+    let mut only_in_b = compute_holidays(year_b)?;
+    let mut diff = HolidayDiff::default();
+    for a in compute_holidays(year_a)? {
+        let Some(i) = only_in_b.iter().position(|b| b.name == a.name) else {
+            diff.only_in_a.push(a);
+            continue;
+        };
+        let b = only_in_b.remove(i);
+        if (a.date.month(), a.date.day()) != (b.date.month(), b.date.day()) {
+            diff.date_changes.push((a, b));
+        }
+    }
+    diff.only_in_b = only_in_b;
+    Ok(diff)
+}
+
+/// Get the holiday that a substitute holiday stands in for
+/// (2024-02-12 gives 建国記念の日 on 2024-02-11).
+///
+/// This is the Sunday holiday in the run of consecutive holidays just before the substitute,
+/// so 2020-05-06 (振替休日(憲法記念日)) gives 憲法記念日 on 2020-05-03.
+/// Returns `None` when `substitute_date` is not a substitute holiday.
+pub fn original_holiday_for(substitute_date: NaiveDate) -> Result<Option<Holiday>, Error> {
+    let holidays = compute_holidays(substitute_date.year() as u32)?;
+    let origin = holidays.iter()
+        .find(|h| h.date == substitute_date && h.substitute)
+        .and_then(|h| h.substitute_for);
+    Ok(origin.and_then(|date| holidays.iter().find(|h| h.date == date && !h.substitute).cloned()))
+}
+
+/// Get the substitute holidays of a year with the holidays they stand in for,
+/// as `(substitute, original)` pairs.
+pub fn substitutes(year: u32) -> Result<Vec<(Holiday, Holiday)>, Error> {
+    let holidays = compute_holidays(year)?;
+    let pairs = holidays.iter()
+        .filter(|h| h.substitute)
+        .filter_map(|h| {
+            let original = holidays.iter().find(|o| Some(o.date) == h.substitute_for && !o.substitute)?;
+            Some((h.clone(), original.clone()))
+        })
+        .collect();
+    Ok(pairs)
+}
+
+/// Count the national holidays of a year, excluding substitute holidays.
+pub fn count_national_holidays(year: u32) -> Result<u32, Error> {
+    Ok(holiday_stats(year)?.national)
+}
+
+/// Count the substitute holidays of a year.
+pub fn count_substitute_holidays(year: u32) -> Result<u32, Error> {
+    Ok(holiday_stats(year)?.substitute)
+}
+
+/// Number of holidays in a year, by kind.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HolidayStats {
+    pub national: u32,
+    pub substitute: u32,
+    pub total: u32,
+}
+
+/// Count the national and substitute holidays of a year.
+pub fn holiday_stats(year: u32) -> Result<HolidayStats, Error> {
+    let holidays = compute_holidays(year)?;
+    let substitute = holidays.iter().filter(|h| h.substitute).count() as u32;
+    let total = holidays.len() as u32;
+    Ok(HolidayStats { national: total - substitute, substitute, total })
+}
+
+/// Get the holidays of a fiscal year (年度), from April 1 of `fy` to March 31 of `fy + 1`.
+///
+/// The result spans two calendar years, so it fails when either `fy` or `fy + 1`
+/// is outside the equinox table.
+pub fn holidays_in_fiscal_year(fy: u32) -> Result<Vec<Holiday>, Error> {
+    let next_year = fy + 1;
+    check_supported_year(next_year)?;
+    let mut holidays: Vec<Holiday> = compute_holidays(fy)?
+        .into_iter()
+        .filter(|h| h.date.month() >= 4)
+        .collect();
+    holidays.extend(compute_holidays(next_year)?.into_iter().filter(|h| h.date.month() < 4));
+    Ok(holidays)
+}
+
+/// Same as `holidays_in_fiscal_year`.
+pub fn fiscal_year_holidays(fy: u32) -> Result<Vec<Holiday>, Error> {
+    holidays_in_fiscal_year(fy)
+}
+
+/// Get the first holiday on or after `from`.
+///
+/// Looks into the following year when no holiday is left in the year of `from`.
+pub fn next_holiday(from: NaiveDate) -> Result<Holiday, Error> {
+    let year = from.year() as u32;
+    if let Some(h) = compute_holidays(year)?.into_iter().find(|h| h.date >= from) {
+        return Ok(h);
+    }
+    let next_year = year + 1;
+    check_supported_year(next_year)?;
+    compute_holidays(next_year)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("no holidays found in {}", next_year))
+}
+
+/// Get the first `count` holidays on or after `from`.
+///
+/// Fails when they run past the equinox table.
+pub fn upcoming_holidays(from: NaiveDate, count: usize) -> Result<Vec<Holiday>, Error> {
+    let mut holidays: Vec<Holiday> = Vec::with_capacity(count);
+    let mut day = from;
+    while holidays.len() < count {
+        let h = next_holiday(day)?;
+        day = h.date + Duration::days(1);
+        holidays.push(h);
+    }
+    Ok(holidays)
+}
+
+// Japan Standard Time has no daylight saving time.
+const JST_OFFSET_SECONDS: i32 = 9 * 3600;
+
+/// Get the date in Japan at an instant.
+pub fn jst_date(instant: DateTime<Utc>) -> NaiveDate {
+    let jst = FixedOffset::east_opt(JST_OFFSET_SECONDS).unwrap();
+    instant.with_timezone(&jst).date_naive()
+}
+
+/// Get today's date in Japan, whatever the time zone of the host.
+pub fn today_jst() -> NaiveDate {
+    jst_date(Utc::now())
+}
+
+/// Get the number of days from `from` until the next holiday.
+///
+/// Returns 0 when `from` is itself a holiday.
+pub fn days_until_next_holiday(from: NaiveDate) -> Result<u32, Error> {
+    let next = next_holiday(from)?;
+    Ok((next.date - from).num_days() as u32)
+}
+
+/// Parse a list of holidays from the JSON produced by `format_holidays(&holidays, OutputFormat::Json)`.
+pub fn parse_json(json: &str) -> Result<Vec<Holiday>, Error> {
+    Ok(serde_json::from_str(json)?)
+}
+
+/// Check whether `date` is a national or substitute holiday.
+///
+/// Fails outside the years of the equinox table, like the other lookups.
+pub fn is_holiday(date: NaiveDate) -> Result<bool, Error> {
+    Ok(cached_holidays(date.year() as u32)?.contains(date))
+}
+
+/// Get the holiday on `date`, if any.
+pub fn holiday_on(date: NaiveDate) -> Result<Option<Holiday>, Error> {
+    Ok(cached_holidays(date.year() as u32)?.get(date).map(|h| h.holiday().clone()))
+}
+
+/// Check whether any holiday falls between `start` and `end`, both inclusive.
+///
+/// Returns `false` when `start` is after `end`.
+pub fn has_holiday_between(start: NaiveDate, end: NaiveDate) -> Result<bool, Error> {
+    for year in start.year()..=end.year() {
+        if cached_holidays(year as u32)?.holidays().iter().any(|h| h.date >= start && h.date <= end) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Get the holidays between `start` and `end`, both inclusive, across years.
+///
+/// Returns an empty list when `start` is after `end`.
+pub fn holidays_between(start: NaiveDate, end: NaiveDate) -> Result<Vec<Holiday>, Error> {
+    let mut holidays = Vec::new();
+    for year in start.year()..=end.year() {
+        holidays.extend(cached_holidays(year as u32)?.holidays().iter().filter(|h| h.date >= start && h.date <= end).cloned());
+    }
+    Ok(holidays)
+}
+
+/// Get the holidays of a month.
+pub fn holidays_in_month(year: u32, month: u32) -> Result<Vec<Holiday>, Error> {
+    let first = NaiveDate::from_ymd_opt(year as i32, month, 1)
+        .ok_or_else(|| anyhow!("invalid month {}-{}", year, month))?;
+    Ok(cached_holidays(year)?.holidays().iter().filter(|h| h.date.month() == first.month()).cloned().collect())
+}
+
+/// Get the holidays of the week containing `date`, with weeks starting on `week_start`
+/// (usually Monday, or Sunday as on most Japanese wall calendars).
+///
+/// A week spanning New Year includes the holidays of both years; days of a year outside
+/// the equinox table are skipped, so only the year of `date` has to be supported.
+pub fn holidays_in_week(date: NaiveDate, week_start: Weekday) -> Result<Vec<Holiday>, Error> {
+    let week = date.week(week_start);
+    let in_table = |d: NaiveDate| check_supported_year(d.year() as u32).is_ok();
+    let start = Some(week.first_day()).filter(|d| in_table(*d))
+        .unwrap_or_else(|| date.with_ordinal(1).unwrap_or(date));
+    let end = Some(week.last_day()).filter(|d| in_table(*d))
+        .unwrap_or_else(|| NaiveDate::from_ymd_opt(date.year(), 12, 31).unwrap_or(date));
+    holidays_between(start, end)
+}
+
+/// A month laid out in week rows for a month view, see `month_grid`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MonthGrid {
+    pub year: u32,
+    pub month: u32,
+    pub weeks: Vec<Vec<GridCell>>, // rows of 7 days starting on the chosen weekday
+}
+
+/// A day of a `MonthGrid`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct GridCell {
+    pub date: NaiveDate,
+    pub in_month: bool, // false for the days of the previous and next month that fill the first and last row
+    pub weekday: Weekday,
+    pub holiday: Option<String>, // name of the holiday on the day
+    pub kind: Option<HolidayType>,
+}
+
+/// Lay out a month in week rows starting on `week_start`, for a month view.
+///
+/// The first and last row are filled with days of the adjacent months, which are marked
+/// with their holidays too, except in years outside the equinox table.
+pub fn month_grid(year: u32, month: u32, week_start: Weekday) -> Result<MonthGrid, Error> {
+    let first = NaiveDate::from_ymd_opt(year as i32, month, 1)
+        .ok_or_else(|| anyhow!("invalid month {}-{}", year, month))?;
+    let last = first.checked_add_months(Months::new(1))
+        .ok_or_else(|| anyhow!("year {} is out of range", year))? - Duration::days(1);
+    let mut weeks = Vec::new();
+    let mut start = first.week(week_start).first_day();
+    while start <= last {
+        let mut row = Vec::with_capacity(7);
+        for date in start.iter_days().take(7) {
+            let in_month = date.month() == month;
+            let holiday = match holiday_on(date) {
+                std::result::Result::Ok(holiday) => holiday,
+                // padding days in a year outside the equinox table
+                Err(_) if !in_month => None,
+                Err(e) => return Err(e),
+            };
+            row.push(GridCell {
+                date,
+                in_month,
+                weekday: date.weekday(),
+                kind: holiday.as_ref().map(|h| h.kind),
+                holiday: holiday.map(|h| h.name),
+            });
+        }
+        weeks.push(row);
+        start += Duration::days(7);
+    }
+    Ok(MonthGrid { year, month, weeks })
+}
+
+/// Check whether `date` is a business day, i.e. neither a weekend nor a holiday.
+///
+/// Fails outside the years of the equinox table.
+///
+/// For another weekend, use `workday::BusinessCalendar`.
+pub fn is_business_day(date: NaiveDate) -> Result<bool, Error> {
+    BusinessCalendar::default().is_business_day(date)
+}
+
+/// Get the working days of a month (weekdays that are not holidays), in order.
+///
+/// `workday::working_days_in_month` counts them.
+pub fn working_dates_in_month(year: u32, month: u32) -> Result<Vec<NaiveDate>, Error> {
+    BusinessCalendar::default().business_days(year, month)
+}
+
+/// Get the first business day after `date`.
+pub fn next_business_day(date: NaiveDate) -> Result<NaiveDate, Error> {
+    BusinessCalendar::default().next_business_day(date)
+}
+
+/// Check whether `date` is part of a run of 3 or more consecutive non-business days.
+///
+/// Fails for dates outside the equinox table; days beyond it count as plain weekdays.
+pub fn is_long_weekend(date: NaiveDate) -> Result<bool, Error> {
+    Ok(off_day_run(date)?.is_some_and(|(start, end)| (end - start).num_days() + 1 >= 3))
+}
+
+/// Get the holidays of a year that fall on `weekday`.
+pub fn holidays_on_weekday(year: u32, weekday: Weekday) -> Result<Vec<Holiday>, Error> {
+    Ok(compute_holidays(year)?.into_iter().filter(|h| h.date.weekday() == weekday).collect())
+}
+
+/// Get the holidays of a year grouped by weekday.
+///
+/// Weekdays without a holiday are absent from the map.
+pub fn holiday_weekday_distribution(year: u32) -> Result<HashMap<Weekday, Vec<Holiday>>, Error> {
+    let mut distribution: HashMap<Weekday, Vec<Holiday>> = HashMap::new();
+    for h in compute_holidays(year)? {
+        distribution.entry(h.date.weekday()).or_default().push(h);
+    }
+    Ok(distribution)
+}
+
+/// Aggregated holiday figures of a year.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub struct YearStats {
+    pub year: u32,
+    pub total: u32,
+    pub substitute: u32,
+    pub citizens: u32, // 国民の休日
+    pub per_month: [u32; 12], // January first
+    pub per_weekday: [u32; 7], // Monday first
+    pub on_saturday: u32, // holidays lost to a Saturday
+    pub longest_break: u32, // longest run of consecutive weekend days and holidays
+    pub eto: String, // 干支 of the year (甲辰)
+}
+
+/// Compute aggregated holiday figures for a year.
+pub fn stats(year: u32) -> Result<YearStats, Error> {
+    let holidays = compute_holidays(year)?;
+    let mut stats = YearStats { year, longest_break: 2, eto: year_eto(year as i32).kanji(), ..YearStats::default() };
+    for h in &holidays {
+        stats.total += 1;
+        if h.substitute {
+            stats.substitute += 1;
+        }
+        if h.name == CITIZENS_HOLIDAY_NAME {
+            stats.citizens += 1;
+        }
+        stats.per_month[h.date.month0() as usize] += 1;
+        stats.per_weekday[h.date.weekday().num_days_from_monday() as usize] += 1;
+        if h.date.weekday() == Weekday::Sat {
+            stats.on_saturday += 1;
+        }
+        if let Some((start, end)) = off_day_run(h.date)? {
+            stats.longest_break = stats.longest_break.max((end - start).num_days() as u32 + 1);
+        }
+    }
+    Ok(stats)
+}
+
+/// Leave days that join holidays and weekends into a longer break.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BridgeSuggestion {
+    pub take: Vec<NaiveDate>, // business days to take off
+    pub start: NaiveDate, // first day of the resulting break
+    pub end: NaiveDate, // last day of the resulting break
+    pub length: u32, // days in the break, `take` included
+}
+
+/// Suggest leave of up to `max_leave` business days that joins runs of weekends and holidays
+/// into one longer break, longest break first.
+///
+/// Every suggested break contains at least one holiday. Runs are cut at the year boundary.
+pub fn bridge_days(year: u32, max_leave: u32) -> Result<Vec<BridgeSuggestion>, Error> {
+    let holidays = compute_holidays(year)?;
+    let first = NaiveDate::from_ymd_opt(year as i32, 1, 1)
+        .ok_or_else(|| anyhow!("year {} is out of range", year))?;
+    let days: Vec<NaiveDate> = first.iter_days().take_while(|d| d.year() == first.year()).collect();
+    let is_off = |d: &NaiveDate| matches!(d.weekday(), Weekday::Sat | Weekday::Sun) || holidays.iter().any(|h| h.date == *d);
+
+    // runs of off days as (start, end)
+    let mut runs: Vec<(NaiveDate, NaiveDate)> = Vec::new();
+    for d in days.iter().filter(|d| is_off(d)) {
+        match runs.last_mut() {
+            Some((_, end)) if *end + Duration::days(1) == *d => *end = *d,
+            _ => runs.push((*d, *d)),
+        }
+    }
+
+    let mut suggestions = Vec::new();
+    for i in 0..runs.len() {
+        let mut take = Vec::new();
+        for j in i + 1..runs.len() {
+            take.extend(runs[j - 1].1.iter_days().skip(1).take_while(|d| *d < runs[j].0));
+            if take.len() as u32 > max_leave {
+                break;
+            }
+            let (start, end) = (runs[i].0, runs[j].1);
+            if !holidays.iter().any(|h| h.date >= start && h.date <= end) {
+                continue;
+            }
+            suggestions.push(BridgeSuggestion {
+                take: take.clone(),
+                start,
+                end,
+                length: (end - start).num_days() as u32 + 1,
+            });
+        }
+    }
+    suggestions.sort_by(|a, b| {
+        b.length.cmp(&a.length)
+            .then_with(|| a.take.len().cmp(&b.take.len()))
+            .then_with(|| a.start.cmp(&b.start))
+    });
+    Ok(suggestions)
 }
 
 // private functions
 
+// Holidays per year, computed once and shared by the lookup functions.
+fn cached_holidays(year: u32) -> Result<Arc<HolidaySet>, Error> {
+    static CACHE: OnceLock<Mutex<HashMap<u32, Arc<HolidaySet>>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(holidays) = cache.lock().map_err(|_| anyhow!("holiday cache is poisoned"))?.get(&year) {
+        return Ok(holidays.clone());
+    }
+    check_supported_year(year)?;
+    let holidays = Arc::new(HolidaySet::new(year)?);
+    cache.lock().map_err(|_| anyhow!("holiday cache is poisoned"))?.insert(year, holidays.clone());
+    Ok(holidays)
+}
+
+// Fail for years without equinox data, whose 春分の日 and 秋分の日 are unknown.
+fn check_supported_year(year: u32) -> Result<(), Error> {
+    if !all_years_with_equinox_data().contains(&year) {
+        return Err(anyhow!("year {} is out of the supported range", year));
+    }
+    Ok(())
+}
+
+// First and last day of the run of non-business days containing `date`,
+// `None` when `date` is a business day.
+//
+// Days past either end of the equinox table count as plain weekdays, so a run at
+// 2020-01-01 or 2050-12-31 stops at the edge instead of failing.
+fn off_day_run(date: NaiveDate) -> Result<Option<(NaiveDate, NaiveDate)>, Error> {
+    if is_business_day(date)? {
+        return Ok(None);
+    }
+    let is_off = |d: NaiveDate| -> Result<bool, Error> {
+        if check_supported_year(d.year() as u32).is_err() {
+            return Ok(WeekendDays::default().contains(d.weekday()));
+        }
+        Ok(!is_business_day(d)?)
+    };
+    let mut start = date;
+    while is_off(start - Duration::days(1))? {
+        start -= Duration::days(1);
+    }
+    let mut end = date;
+    while is_off(end + Duration::days(1))? {
+        end += Duration::days(1);
+    }
+    Ok(Some((start, end)))
+}
+
+// 国民の休日: a day sandwiched between two national holidays (since 1988).
+fn citizens_holiday_adjustment(year: u32, data: &mut Vec<Holiday>) {
+    if year < 1988 {
+        return;
+    }
+    let dates: Vec<NaiveDate> = data.iter().map(|h| h.date).collect();
+    for date in &dates {
+        let candidate = *date + Duration::days(1);
+        if dates.contains(&candidate) || !dates.contains(&(candidate + Duration::days(1))) {
+            continue;
+        }
+        // Sundays are already days off, and the day after a Sunday holiday
+        // becomes a substitute holiday instead.
+        if candidate.weekday() == Weekday::Sun || date.weekday() == Weekday::Sun {
+            continue;
+        }
+        data.push(Holiday {
+            name: CITIZENS_HOLIDAY_NAME.to_string(),
+            date: candidate,
+            substitute: false,
+            kind: HolidayType::National,
+            substitute_for: None,
+            id: CITIZENS_HOLIDAY_ID.to_string(),
+            note: None,
+        });
+    }
+}
+
+// substitute:sports-day
+fn substitute_id(original_id: &str) -> String {
+    format!("substitute:{}", original_id)
+}
+
+// custom:創立記念日, so custom holidays can be translated like statutory ones
+fn custom_id(name: &str) -> String {
+    format!("custom:{}", name)
+}
+
+// `data` must be sorted by date.
 fn substitute_adjustment(data: &mut Vec<Holiday>) {
+   // substitute holidays were introduced on 1973/4/12
+   let introduced = NaiveDate::from_ymd_opt(1973, 4, 12).unwrap();
    let mut i:usize = 0;
    while i < data.len() {
+        if data[i].date < introduced {
+            i += 1;
+            continue;
+        }
+        // before 2007 only the day right after a Sunday holiday could be a substitute
+        if data[i].date.weekday() == Weekday::Sun && data[i].date.year() < 2007 {
+            let sub_date = data[i].date + Duration::days(1);
+            if !data.iter().any(|h:&Holiday| h.date == sub_date) {
+                data.push(Holiday {
+                    name: format!("振替休日({})", data[i].name),
+                    date: sub_date,
+                    substitute: true,
+                    kind: HolidayType::National,
+                    substitute_for: Some(data[i].date),
+                    id: substitute_id(&data[i].id),
+                    note: None,
+                });
+            }
         // if it a Sunday
-        if data[i].date.weekday() == Weekday::Sun {
+        } else if data[i].date.weekday() == Weekday::Sun {
+            // named after the Sunday holiday, not the last one of the run (2020-05-06 is 振替休日(憲法記念日))
+            let (sunday, sunday_name, sunday_id) = (data[i].date, data[i].name.clone(), data[i].id.clone());
             let mut last_holiday_date = data[i].date;
             while let Some(next_holiday) = data.get(i+1) {
                 if next_holiday.date == last_holiday_date + Duration::days(1) {
@@ -47,13 +1167,17 @@ fn substitute_adjustment(data: &mut Vec<Holiday>) {
             }
             let mut sub_date = last_holiday_date + Duration::days(1);
             while data.iter().any(|h:&Holiday| h.date == sub_date) {
-                sub_date = sub_date + Duration::days(1);
+                sub_date += Duration::days(1);
             }
 
             data.push(Holiday {
-                name: format!("振替休日({})", data[i].name),
+                name: format!("振替休日({})", sunday_name),
                 date: sub_date,
                 substitute: true,
+                kind: HolidayType::National,
+                substitute_for: Some(sunday),
+                id: substitute_id(&sunday_id),
+                note: None,
             });
         }
         i += 1;
@@ -61,100 +1185,493 @@ fn substitute_adjustment(data: &mut Vec<Holiday>) {
 }
 
 
-fn pick_exuinox_from_year(year:u32) -> Vec<Holiday> {
-    if year < 2020 || year > 2050 {
-        return Vec::new();
+fn equinox_id(name: &str) -> &'static str {
+    match name {
+        VERNAL_EQUINOX_NAME => "vernal-equinox-day",
+        _ => "autumnal-equinox-day",
     }
-    let equinoxes = get_equinox_dates().unwrap();
-    let target = equinoxes.into_iter().find(|x| x.year == year);
+}
+
+// A year missing from the table gets a warning, so `compute_holidays` fails rather than
+// returning a year without 春分の日 and 秋分の日.
+fn pick_exuinox_from_year(year:u32, warnings: &mut Vec<ParseWarning>) -> Vec<Holiday> {
+    let (equinoxes, parse_warnings) = equinox_table();
+    warnings.extend(parse_warnings.iter().cloned());
     let mut return_value: Vec<Holiday> = Vec::new();
-    match target {
-        Some(v) => {
-            v.equinox.into_iter().for_each(|x| {
-                return_value.push(Holiday {
-                    name: x.name,
-                    date: NaiveDate::parse_from_str(&format!("{}/{}", year, x.date).to_string(), "%Y/%m/%d").unwrap(),
-                    substitute: false,
-                });
-            })
-        },
-        None => {},
+    let Some(v) = equinoxes.get(&year) else {
+        warnings.push(ParseWarning::new(None, anyhow!("no equinox data for {}: 春分の日 and 秋分の日 are missing", year)));
+        return return_value;
+    };
+    for x in &v.equinox {
+        return_value.push(Holiday {
+            name: x.name.clone(),
+            date: x.parsed,
+            substitute: false,
+            kind: HolidayType::National,
+            substitute_for: None,
+            id: equinox_id(&x.name).to_string(),
+            note: None,
+        });
     }
 
     return_value
-
-
 }
 
 // for base dates
-fn prepara(year: u32)->Vec<Holiday> {
-    let dataset = get_schedule().unwrap();
+fn prepara(year: u32, dataset: Vec<BaseHolyday>, warnings: &mut Vec<ParseWarning>) -> Vec<Holiday> {
     let mut days: Vec<Holiday> = Vec::new();
     for d in dataset {
-        if d.relative {
-            let relative_date = get_relative_date(year, d.condition.unwrap()).unwrap();
-            days.push(Holiday {
-                name: d.name,
-                date: relative_date.format("%Y-%m-%d").to_string().parse::<NaiveDate>().unwrap(),
-                substitute: false,
-            })
+        let date = if d.relative {
+            d.condition
+                .ok_or_else(|| anyhow!("row '{}': relative holiday without a condition", d.name))
+                .and_then(|condition| get_relative_date(year, condition)
+                    .with_context(|| format!("row '{}': invalid condition", d.name)))
         } else {
-            days.push(Holiday {
+            d.date
+                .ok_or_else(|| anyhow!("row '{}': missing date", d.name))
+                .and_then(|raw| parse_holiday_date(&raw, Some(year))
+                    .with_context(|| format!("row '{}': invalid date '{}'", d.name, raw)))
+        };
+        match date {
+            std::result::Result::Ok(date) => days.push(Holiday {
                 name: d.name,
-                date: NaiveDate::parse_from_str(&format!("{}/{}", year, d.date.unwrap()).to_string()
-                , "%Y/%m/%d").unwrap(),
+                date,
                 substitute: false,
-            })
+                kind: HolidayType::National,
+                substitute_for: None,
+                id: d.id,
+                note: d.note,
+            }),
+            Err(e) => warnings.push(ParseWarning::new(None, e)),
+        }
+    }
+    let (specials, mut parse_warnings) = get_special_holidays_with_warnings();
+    warnings.append(&mut parse_warnings);
+    for d in specials.into_iter().filter(|d| d.year == year) {
+        let date = match parse_holiday_date(&d.date, Some(year)) {
+            std::result::Result::Ok(date) => date,
+            Err(e) => {
+                warnings.push(ParseWarning::new(None, e.context(format!("row '{}': invalid date '{}'", d.name, d.date))));
+                continue;
+            }
+        };
+        // a row named after a regular holiday moves it (the 2020/2021 Olympics)
+        if let Some(h) = days.iter_mut().find(|h| h.name == d.name) {
+            h.date = date;
+            continue;
         }
+        days.push(Holiday {
+            name: d.name,
+            date,
+            substitute: false,
+            kind: HolidayType::National,
+            substitute_for: None,
+            id: d.id,
+            note: None,
+        })
     }
     days
 }
 
-// for relative date comvart Datetime
-fn get_relative_date(year: u32, condition: Condition)-> Option<DateTime<Local>> {
-    let month = get_month_num_from_string(&condition.month).unwrap();
-    let weekday = get_weekday_from_string(&condition.weekday).unwrap();
-    let n = condition.n;
-    let mut dates: Vec<DateTime<Local>> = Vec::new();
-    let mut day:DateTime<Local> = Local.with_ymd_and_hms(year as i32, month, 1, 0, 0, 0).unwrap();
+// for relative date: the n-th weekday of the month, `-1` for the last one
+fn get_relative_date(year: u32, condition: Condition)-> Result<NaiveDate, Error> {
+    let month = get_month_num_from_string(&condition.month)
+        .ok_or_else(|| anyhow!("invalid month '{}'", condition.month))?;
+    let weekday = get_weekday_from_string(&condition.weekday)
+        .ok_or_else(|| anyhow!("invalid weekday '{}'", condition.weekday))?;
+    let n = NthWeek::try_from(condition.n)?;
+    nth_weekday_of_month(year as i32, month, weekday, n)
+        .ok_or_else(|| anyhow!("there is no {} {} in {}/{}", condition.n, condition.weekday, year, month))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datebook::timebase::parse_schedule_with_warnings;
+    use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    // Golden Week, 4/29 to 5/6, of a year outside the equinox table
+    fn golden_week(year: i32) -> Vec<(NaiveDate, String)> {
+        let (holidays, warnings) = compute_holidays_with_warnings(year as u32);
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(warnings[0].message.contains("no equinox data"));
+        holidays.into_iter()
+            .filter(|h| h.date >= date(year, 4, 29) && h.date <= date(year, 5, 6))
+            .map(|h| (h.date, h.name))
+            .collect()
+    }
+
+    #[test]
+    fn golden_week_before_and_after_the_2007_reorganization() {
+        assert_eq!(golden_week(1990), [
+            (date(1990, 4, 29), "みどりの日".to_string()),
+            (date(1990, 4, 30), "振替休日(みどりの日)".to_string()),
+            (date(1990, 5, 3), "憲法記念日".to_string()),
+            (date(1990, 5, 4), "国民の休日".to_string()),
+            (date(1990, 5, 5), "こどもの日".to_string()),
+        ]);
+        assert_eq!(golden_week(2006), [
+            (date(2006, 4, 29), "みどりの日".to_string()),
+            (date(2006, 5, 3), "憲法記念日".to_string()),
+            (date(2006, 5, 4), "国民の休日".to_string()),
+            (date(2006, 5, 5), "こどもの日".to_string()),
+        ]);
+        assert_eq!(golden_week(2007), [
+            (date(2007, 4, 29), "昭和の日".to_string()),
+            (date(2007, 4, 30), "振替休日(昭和の日)".to_string()),
+            (date(2007, 5, 3), "憲法記念日".to_string()),
+            (date(2007, 5, 4), "みどりの日".to_string()),
+            (date(2007, 5, 5), "こどもの日".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn years_without_equinox_data_fail() {
+        assert!(compute_holidays(2007).is_err());
+        assert!(compute_holidays(2051).is_err());
+        assert!(compute_holidays(2020).is_ok());
+    }
+
+    const MALFORMED_BASE_CSV: &[u8] = "name,date,relative,condition,effective_from,effective_until,id
+元旦,01/01,false,,1949,,new-years-day
+こどもの日,5/32,false,,1949,,childrens-day
+海の日,,true,july:x:monday,2003,,marine-day
+文化の日,11/3,false,,1948,,culture-day
+".as_bytes();
+
+    #[test]
+    fn malformed_rows_are_skipped_with_a_warning() {
+        let schedule = parse_schedule_with_warnings(MALFORMED_BASE_CSV);
+        let (holidays, warnings) = compute_holidays_from(2024, schedule);
+        let names: Vec<&str> = holidays.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, ["元旦", "春分の日", "秋分の日", "振替休日(秋分の日)", "文化の日", "振替休日(文化の日)"]);
+        let messages: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+        assert_eq!(messages, [
+            "line 4: row '海の日': invalid n 'x': invalid digit found in string",
+            "row 'こどもの日': invalid date '5/32': 2024/5/32 is not a valid date",
+        ]);
+    }
+
+    #[test]
+    fn strict_computation_reports_every_warning() {
+        let schedule = parse_schedule_with_warnings(MALFORMED_BASE_CSV);
+        let error = strict(compute_holidays_from(2024, schedule)).unwrap_err().to_string();
+        assert!(error.contains("'海の日'") && error.contains("'こどもの日'"), "{}", error);
+    }
+
+    #[test]
+    fn lookups_fail_outside_the_equinox_table() {
+        assert!(is_holiday(date(2019, 3, 21)).is_err());
+        assert!(is_holiday(date(2051, 1, 1)).is_err());
+        assert!(is_business_day(date(2051, 1, 4)).is_err());
+        assert!(holiday_on(date(2019, 3, 21)).is_err());
+    }
+
+    #[test]
+    fn business_days_skip_weekends_and_holidays() {
+        assert!(!is_business_day(date(2024, 1, 1)).unwrap()); // 元旦
+        assert!(is_business_day(date(2024, 1, 2)).unwrap());
+        assert!(!is_business_day(date(2024, 1, 6)).unwrap()); // Saturday
+        assert!(!is_business_day(date(2024, 2, 12)).unwrap()); // 振替休日
+        assert!(is_holiday(date(2024, 3, 20)).unwrap()); // 春分の日
+    }
+
+    #[test]
+    fn substitute_after_a_run_is_named_after_the_sunday_holiday() {
+        // 5/3 憲法記念日 is a Sunday; 5/4 and 5/5 are holidays too
+        let substitute = holiday_on(date(2020, 5, 6)).unwrap().unwrap();
+        assert_eq!(substitute.name, "振替休日(憲法記念日)");
+        assert_eq!(substitute.substitute_for, Some(date(2020, 5, 3)));
+        let original = original_holiday_for(date(2020, 5, 6)).unwrap().unwrap();
+        assert_eq!(original.name, "憲法記念日");
+    }
+
+    #[test]
+    fn substitute_id_matches_its_name() {
+        let substitute = holiday_on(date(2020, 5, 6)).unwrap().unwrap();
+        assert_eq!(substitute.id, "substitute:constitution-memorial-day");
+    }
+
+    #[test]
+    fn custom_holidays_and_their_substitutes_have_ids() {
+        // 2024-09-01 is a Sunday
+        let custom = CustomHolidays::new().annual("創立記念日", 9, 1).substitutes(true);
+        let calendar = HolidayCalendar::with_custom(2024, &custom).unwrap();
+        let ids: Vec<(&str, &str)> = calendar.holidays().iter()
+            .filter(|h| h.kind == HolidayType::Custom)
+            .map(|h| (h.name.as_str(), h.id.as_str()))
+            .collect();
+        assert_eq!(ids, [
+            ("創立記念日", "custom:創立記念日"),
+            ("振替休日(創立記念日)", "substitute:custom:創立記念日"),
+        ]);
+    }
+
+    #[test]
+    fn working_dates_of_january_2024() {
+        // 23 weekdays less 1/1 元日 and 1/8 成人の日; the request's 23 forgot the holidays
+        let dates = working_dates_in_month(2024, 1).unwrap();
+        assert_eq!(dates.len(), 21);
+        assert_eq!(crate::datebook::workday::working_days_in_month(2024, 1).unwrap(), 21);
+        assert_eq!((dates[0], dates[20]), (date(2024, 1, 2), date(2024, 1, 31)));
+    }
+
+    #[test]
+    fn holiday_set_agrees_with_compute_holidays() {
+        for year in 2020..=2050 {
+            let holidays = compute_holidays(year).unwrap();
+            let set = HolidaySet::new(year).unwrap();
+            let first = date(year as i32, 1, 1);
+            for day in first.iter_days().take_while(|d| d.year() == year as i32) {
+                let expected = holidays.iter().find(|h| h.date == day);
+                assert_eq!(set.contains(day), expected.is_some(), "{}", day);
+                assert_eq!(set.get(day).map(|h| h.holiday()), expected, "{}", day);
+            }
+        }
+    }
+
+    #[test]
+    fn jst_date_turns_over_at_15_00_utc() {
+        let at = |h, m| NaiveDate::from_ymd_opt(2024, 12, 31).unwrap().and_hms_opt(h, m, 0).unwrap().and_utc();
+        assert_eq!(jst_date(at(14, 59)), date(2024, 12, 31));
+        assert_eq!(jst_date(at(15, 0)), date(2025, 1, 1));
+    }
+
+    #[test]
+    fn rendered_json_parses_back() {
+        use crate::datebook::render::{format_holidays, format_holidays_with_options, Field, OutputFormat, RenderOptions};
+        let holidays = compute_holidays(2024).unwrap();
+        let json = format_holidays(&holidays, OutputFormat::Json).unwrap();
+        let essentials: Vec<Holiday> = holidays.iter()
+            .map(|h| Holiday { name: h.name.clone(), date: h.date, substitute: h.substitute, substitute_for: h.substitute_for, id: h.id.clone(), note: h.note.clone(), ..Holiday::default() })
+            .collect();
+        assert_eq!(parse_json(&json).unwrap(), essentials);
+
+        let fields = vec![Field::Name, Field::Date, Field::Substitute, Field::SubstituteFor, Field::Id, Field::Note];
+        let options = RenderOptions { fields: Some(fields), ..RenderOptions::default() };
+        let json = format_holidays_with_options(&holidays, OutputFormat::Json, &options).unwrap();
+        assert_eq!(parse_json(&json).unwrap(), holidays);
+    }
+
+    #[test]
+    fn missing_fifth_weekday_is_an_error() {
+        // February 2024 has four Mondays
+        let fifth = |month: &str| Condition { month: month.to_string(), n: 5, weekday: "monday".to_string() };
+        let err = get_relative_date(2024, fifth("february")).unwrap_err();
+        assert_eq!(err.to_string(), "there is no 5 monday in 2024/2");
+        assert_eq!(get_relative_date(2024, fifth("april")).unwrap(), date(2024, 4, 29));
+    }
+
+    #[test]
+    fn last_weekday_conditions() {
+        let schedule = parse_schedule_with_warnings("name,date,relative,condition,effective_from,effective_until,id
+月末の日,,true,may:last:monday,2000,,
+二月末の日,,true,february:last:thursday,2000,,
+".as_bytes());
+        let (holidays, warnings) = compute_holidays_from(2024, schedule);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        let dates: Vec<NaiveDate> = holidays.iter().filter(|h| h.name.contains("末の日")).map(|h| h.date).collect();
+        assert_eq!(dates, [date(2024, 2, 29), date(2024, 5, 27)]);
+    }
 
-    while day.month() == month {
-        if day.weekday() == weekday {
-            dates.push(day);
+    #[test]
+    fn out_of_range_n_is_an_error() {
+        for n in [0, 6, -2] {
+            let condition = Condition { month: "may".to_string(), n, weekday: "monday".to_string() };
+            let err = get_relative_date(2024, condition).unwrap_err();
+            assert_eq!(err.to_string(), format!("invalid week {} (expected 1-5 or -1 for the last)", n));
         }
-        day = day + Duration::days(1);
     }
 
-    Some(dates[n as usize -1])
-}
+    #[test]
+    fn substitutes_can_be_split_out() {
+        let substitutes: Vec<NaiveDate> = substitute_holidays_only(2024).unwrap().iter().map(|h| h.date).collect();
+        assert_eq!(substitutes, [date(2024, 2, 12), date(2024, 5, 6), date(2024, 8, 12), date(2024, 9, 23), date(2024, 11, 4)]);
+        let primary = holidays_excluding_substitutes(2024).unwrap();
+        assert_eq!(primary.len(), 16);
+        assert!(primary.iter().all(|h| !h.substitute));
+    }
 
-fn get_weekday_from_string(char: &str)-> Option<Weekday> {
-    match char.trim().to_lowercase().as_str() {
-        "monday" | "mon" => Some(Weekday::Mon),
-        "tuesday" | "tue" => Some(Weekday::Tue),
-        "wednesday" | "wed" => Some(Weekday::Wed),
-        "thursday" | "thu" => Some(Weekday::Thu),
-        "friday" | "fri" => Some(Weekday::Fri),
-        "saturday" | "sat" => Some(Weekday::Sat),
-        "sunday" | "sun" => Some(Weekday::Sun),
-        _ => None,
+    #[test]
+    fn special_holidays_of_2019() {
+        let (holidays, _) = compute_holidays_with_warnings(2019);
+        let name_on = |d: NaiveDate| holidays.iter().find(|h| h.date == d).map(|h| h.name.as_str());
+        assert_eq!(name_on(date(2019, 4, 30)), Some("国民の休日"));
+        assert_eq!(name_on(date(2019, 5, 1)), Some("天皇の即位の日"));
+        assert_eq!(name_on(date(2019, 5, 2)), Some("国民の休日"));
+        assert_eq!(name_on(date(2019, 10, 22)), Some("即位礼正殿の儀"));
+        assert_eq!(name_on(date(2019, 10, 21)), None);
     }
-}
-fn get_month_num_from_string(char: &str) -> Option<u32> {
-    match char.trim().to_lowercase().as_str() {
-        "january" | "jan" => Some(1),
-        "february" | "feb" => Some(2),
-        "march" | "mar" => Some(3),
-        "april" | "apr" => Some(4),
-        "may" => Some(5),
-        "june" | "jun" => Some(6),
-        "july" | "jul" => Some(7),
-        "august" | "aug" => Some(8),
-        "september" | "sep" => Some(9),
-        "october" | "oct" => Some(10),
-        "november" | "nov" => Some(11),
-        "december" | "dec" => Some(12),
-        _ => None,
+
+    #[test]
+    fn era_year_changes_on_2019_05_01() {
+        let (holidays, _) = compute_holidays_with_warnings(2019);
+        let era_on = |d: NaiveDate| holidays.iter().find(|h| h.date == d).and_then(|h| h.era_year());
+        assert_eq!(era_on(date(2019, 4, 30)), Some((EraName::Heisei, 31)));
+        assert_eq!(era_on(date(2019, 5, 1)), Some((EraName::Reiwa, 1)));
+        assert_eq!(Holiday { date: date(1868, 1, 1), ..Holiday::default() }.era_year(), None);
+    }
+
+    #[test]
+    fn diff_of_2020_and_2021() {
+        let diff = holidays_diff(2020, 2021).unwrap();
+        let names = |holidays: &[Holiday]| holidays.iter().map(|h| h.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&diff.only_in_a), ["振替休日(天皇誕生日)", "振替休日(憲法記念日)"]);
+        assert_eq!(names(&diff.only_in_b), ["振替休日(山の日)"]);
+        let changes: Vec<(&str, NaiveDate, NaiveDate)> = diff.date_changes.iter().map(|(a, b)| (a.name.as_str(), a.date, b.date)).collect();
+        assert_eq!(changes, [
+            ("成人の日", date(2020, 1, 13), date(2021, 1, 11)),
+            ("海の日", date(2020, 7, 23), date(2021, 7, 22)),
+            ("スポーツの日", date(2020, 7, 24), date(2021, 7, 23)),
+            ("山の日", date(2020, 8, 10), date(2021, 8, 8)),
+            ("敬老の日", date(2020, 9, 21), date(2021, 9, 20)),
+            ("秋分の日", date(2020, 9, 22), date(2021, 9, 23)),
+        ]);
+    }
+
+    #[test]
+    fn olympics_moves_of_2021() {
+        let holidays = compute_holidays(2021).unwrap();
+        let date_of = |name: &str| holidays.iter().find(|h| h.name == name).map(|h| h.date);
+        assert_eq!(date_of("海の日"), Some(date(2021, 7, 22)));
+        assert_eq!(date_of("スポーツの日"), Some(date(2021, 7, 23)));
+        assert_eq!(date_of("山の日"), Some(date(2021, 8, 8)));
+        assert_eq!(date_of("振替休日(山の日)"), Some(date(2021, 8, 9)));
+        assert!(!holidays.iter().any(|h| h.date == date(2021, 7, 19) || h.date == date(2021, 10, 11)));
     }
-}
 
+    #[test]
+    fn observances_are_opt_in() {
+        let on_march_3 = |include_observances| {
+            let config = HolidayConfig { include_observances, ..HolidayConfig::default() };
+            compute_holidays_with_config(2024, &config).unwrap().into_iter()
+                .find(|h| h.date == date(2024, 3, 3))
+                .map(|h| (h.name, h.kind))
+        };
+        assert_eq!(on_march_3(true), Some(("ひな祭り".to_string(), HolidayType::Observance)));
+        assert_eq!(on_march_3(false), None);
+    }
+
+    #[test]
+    fn fiscal_year_2024() {
+        let holidays = fiscal_year_holidays(2024).unwrap();
+        assert_eq!(holidays, holidays_in_fiscal_year(2024).unwrap());
+        // 15 holidays from April to December 2024 and 6 from January to March 2025
+        assert_eq!(holidays.len(), 21);
+        assert_eq!(holidays.first().map(|h| h.date), Some(date(2024, 4, 29)));
+        assert_eq!(holidays.last().map(|h| h.date), Some(date(2025, 3, 20)));
+        // March 2051 is outside the equinox table
+        assert!(fiscal_year_holidays(2050).is_err());
+    }
+
+    #[test]
+    fn monday_holidays_of_2024() {
+        // 元日, 成人の日, 昭和の日, 海の日, 敬老の日, スポーツの日 and five substitute holidays
+        let mondays = holidays_on_weekday(2024, Weekday::Mon).unwrap();
+        assert_eq!(mondays.len(), 11);
+        assert_eq!(mondays.iter().filter(|h| h.substitute).count(), 5);
+        let distribution = holiday_weekday_distribution(2024).unwrap();
+        assert_eq!(distribution[&Weekday::Mon], mondays);
+        assert_eq!(distribution.values().map(Vec::len).sum::<usize>(), 21);
+    }
+
+    #[test]
+    fn month_grid_of_february_2024() {
+        let grid = month_grid(2024, 2, Weekday::Mon).unwrap();
+        assert_eq!(grid.weeks.len(), 5);
+        assert!(grid.weeks.iter().all(|week| week.len() == 7));
+        assert_eq!((grid.weeks[0][0].date, grid.weeks[0][0].in_month), (date(2024, 1, 29), false));
+        assert_eq!((grid.weeks[4][3].date, grid.weeks[4][3].in_month), (date(2024, 2, 29), true));
+        assert_eq!(grid.weeks[4][6].date, date(2024, 3, 3));
+        let holidays: Vec<(NaiveDate, &str)> = grid.weeks.iter().flatten()
+            .filter_map(|cell| Some((cell.date, cell.holiday.as_deref()?)))
+            .collect();
+        assert_eq!(holidays, [
+            (date(2024, 2, 11), "建国記念の日"),
+            (date(2024, 2, 12), "振替休日(建国記念の日)"),
+            (date(2024, 2, 23), "天皇誕生日"),
+        ]);
+    }
+
+    #[test]
+    fn month_grid_starting_on_the_first() {
+        // 2024-09-01 is a Sunday
+        let grid = month_grid(2024, 9, Weekday::Sun).unwrap();
+        assert_eq!(grid.weeks[0][0].date, date(2024, 9, 1));
+        assert!(grid.weeks[0].iter().all(|cell| cell.in_month));
+        assert_eq!(grid.weeks.len(), 5);
+        assert_eq!(month_grid(2024, 9, Weekday::Mon).unwrap().weeks[0][0].date, date(2024, 8, 26));
+    }
+
+    #[test]
+    fn stats_at_the_edges_of_the_equinox_table() {
+        // 2020-01-01 is a Wednesday; the walk back stops at 2019-12-31
+        let first = stats(2020).unwrap();
+        assert_eq!((first.total, first.substitute, first.longest_break), (18, 2, 5));
+        let last = stats(2050).unwrap();
+        assert_eq!(last.year, 2050);
+        assert_eq!(last.total, compute_holidays(2050).unwrap().len() as u32);
+    }
+
+    #[test]
+    fn long_weekends_at_the_edges_of_the_equinox_table() {
+        assert!(!is_long_weekend(date(2020, 1, 1)).unwrap());
+        // Saturday and the Sunday of 2051, whose holidays are unknown
+        assert!(!is_long_weekend(date(2050, 12, 31)).unwrap());
+        assert!(is_long_weekend(date(2019, 12, 31)).is_err());
+    }
+
+    #[test]
+    fn month_grid_at_the_edges_of_the_equinox_table() {
+        let january = month_grid(2020, 1, Weekday::Mon).unwrap();
+        assert_eq!((january.weeks[0][0].date, january.weeks[0][0].holiday.clone()), (date(2019, 12, 30), None));
+        assert_eq!(january.weeks[0][2].holiday.as_deref(), Some("元旦"));
+        let december = month_grid(2050, 12, Weekday::Mon).unwrap();
+        let last = december.weeks.last().unwrap();
+        assert_eq!((last[6].date, last[6].holiday.clone()), (date(2051, 1, 1), None));
+        assert!(month_grid(2019, 12, Weekday::Mon).is_err());
+    }
+
+    #[test]
+    fn weeks_at_the_edges_of_the_equinox_table() {
+        let names = |holidays: Vec<Holiday>| holidays.into_iter().map(|h| h.name).collect::<Vec<_>>();
+        // 2019-12-30 to 2020-01-05
+        assert_eq!(names(holidays_in_week(date(2020, 1, 1), Weekday::Mon).unwrap()), ["元旦"]);
+        // 2050-12-26 to 2051-01-01
+        assert!(holidays_in_week(date(2050, 12, 31), Weekday::Mon).unwrap().is_empty());
+        assert!(holidays_in_week(date(2019, 12, 31), Weekday::Mon).is_err());
+        // a week spanning two supported years
+        assert_eq!(names(holidays_in_week(date(2024, 12, 31), Weekday::Mon).unwrap()), ["元旦"]);
+    }
+
+    proptest! {
+        #[test]
+        fn holidays_are_sorted_unique_and_substitutes_are_well_placed(year in 2020u32..=2050) {
+            let holidays = compute_holidays(year).unwrap();
+            // strictly increasing dates: sorted, and no other holiday on a substitute's date
+            for pair in holidays.windows(2) {
+                prop_assert!(pair[0].date < pair[1].date, "{} is not before {}", pair[0].date, pair[1].date);
+            }
+            for h in holidays.iter().filter(|h| h.substitute) {
+                let original = h.substitute_for.unwrap();
+                prop_assert!(original < h.date, "{} is not after {}", h.date, original);
+                prop_assert_eq!(original.weekday(), Weekday::Sun);
+                prop_assert!(!matches!(h.date.weekday(), Weekday::Sat | Weekday::Sun), "{} is on a weekend", h.date);
+            }
+        }
+
+        #[test]
+        fn every_sunday_holiday_has_exactly_one_substitute(year in 2020u32..=2050) {
+            let holidays = compute_holidays(year).unwrap();
+            for h in holidays.iter().filter(|h| !h.substitute && h.date.weekday() == Weekday::Sun) {
+                let substitutes = holidays.iter().filter(|s| s.substitute && s.substitute_for == Some(h.date)).count();
+                prop_assert_eq!(substitutes, 1, "{} on {}", h.name, h.date);
+            }
+        }
+    }
+}
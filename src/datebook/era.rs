@@ -0,0 +1,82 @@
+//! # Era
+//! Conversion of dates to Japanese eras (元号), e.g. 2024-01-01 is 令和6年.
+//!
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// Japanese era
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum EraName {
+    Meiji,
+    Taisho,
+    Showa,
+    Heisei,
+    Reiwa,
+}
+
+impl EraName {
+    /// Name of the era in kanji (令和).
+    pub fn kanji(&self) -> &'static str {
+        match self {
+            EraName::Meiji => "明治",
+            EraName::Taisho => "大正",
+            EraName::Showa => "昭和",
+            EraName::Heisei => "平成",
+            EraName::Reiwa => "令和",
+        }
+    }
+
+    /// First day of the era.
+    pub fn start(&self) -> NaiveDate {
+        let (y, m, d) = match self {
+            EraName::Meiji => (1868, 10, 23),
+            EraName::Taisho => (1912, 7, 30),
+            EraName::Showa => (1926, 12, 25),
+            EraName::Heisei => (1989, 1, 8),
+            EraName::Reiwa => (2019, 5, 1),
+        };
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+}
+
+const ERAS: [EraName; 5] = [EraName::Reiwa, EraName::Heisei, EraName::Showa, EraName::Taisho, EraName::Meiji];
+
+/// Get the era and the era year of a date (2019-05-01 is `(Reiwa, 1)`).
+///
+/// Returns `None` for dates before the Meiji era.
+pub fn era_of(date: NaiveDate) -> Option<(EraName, u32)> {
+    let era = ERAS.into_iter().find(|e| date >= e.start())?;
+    Some((era, (date.year() - era.start().year() + 1) as u32))
+}
+
+/// Format a date in the Japanese calendar (和暦), e.g. `令和6年1月1日` or `令和元年5月1日`.
+///
+/// Dates before the Meiji era are written with the Gregorian year.
+pub fn wareki(date: NaiveDate) -> String {
+    match era_of(date) {
+        Some((era, 1)) => format!("{}元年{}月{}日", era.kanji(), date.month(), date.day()),
+        Some((era, year)) => format!("{}{}年{}月{}日", era.kanji(), year, date.month(), date.day()),
+        None => format!("{}年{}月{}日", date.year(), date.month(), date.day()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    // `get_japanese_era` returns these through `era_of`
+    #[test]
+    fn era_boundaries() {
+        assert_eq!(era_of(date(2019, 4, 30)), Some((EraName::Heisei, 31)));
+        assert_eq!(era_of(date(2019, 5, 1)), Some((EraName::Reiwa, 1)));
+        assert_eq!(era_of(date(1989, 1, 7)), Some((EraName::Showa, 64)));
+        assert_eq!(era_of(date(1989, 1, 8)), Some((EraName::Heisei, 1)));
+        assert_eq!(era_of(date(1868, 10, 22)), None);
+        assert_eq!(wareki(date(2019, 5, 1)), "令和元年5月1日");
+    }
+}
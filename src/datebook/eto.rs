@@ -0,0 +1,107 @@
+//! # Eto
+//! The sexagenary cycle (干支, 十干十二支) of years and days, e.g. 2024 is 甲辰.
+//!
+//! Years change their 干支 on January 1, as on Japanese calendars, not at 立春.
+//!
+use std::fmt;
+use chrono::{Datelike, NaiveDate};
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+const STEMS: [&str; 10] = ["甲", "乙", "丙", "丁", "戊", "己", "庚", "辛", "壬", "癸"];
+const BRANCHES: [&str; 12] = ["子", "丑", "寅", "卯", "辰", "巳", "午", "未", "申", "酉", "戌", "亥"];
+const ANIMALS: [&str; 12] = ["鼠", "牛", "虎", "兎", "竜", "蛇", "馬", "羊", "猿", "鶏", "犬", "猪"];
+
+// 1984 (甲子) is the start of the current year cycle
+const YEAR_EPOCH: i32 = 1984;
+// `num_days_from_ce` of a 甲子 day (the Julian day number plus 49 is a multiple of 60)
+const DAY_OFFSET: i32 = 1721474;
+
+/// A position in the 60-step cycle, 甲子 first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Eto {
+    index: u8, // 0 (甲子) to 59 (癸亥)
+}
+
+impl Eto {
+    fn new(n: i32) -> Self {
+        Eto { index: n.rem_euclid(60) as u8 }
+    }
+
+    /// Position in the cycle, 0 for 甲子 and 59 for 癸亥.
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Heavenly stem (十干), e.g. 甲.
+    pub fn stem(&self) -> &'static str {
+        STEMS[self.index as usize % 10]
+    }
+
+    /// Earthly branch (十二支), e.g. 辰.
+    pub fn branch(&self) -> &'static str {
+        BRANCHES[self.index as usize % 12]
+    }
+
+    /// Zodiac animal of the branch, e.g. 竜 for 辰.
+    pub fn animal(&self) -> &'static str {
+        ANIMALS[self.index as usize % 12]
+    }
+
+    /// Stem and branch, e.g. 甲辰.
+    pub fn kanji(&self) -> String {
+        format!("{}{}", self.stem(), self.branch())
+    }
+}
+
+impl fmt::Display for Eto {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.stem(), self.branch())
+    }
+}
+
+// { kanji: "甲辰", stem: "甲", branch: "辰", animal: "竜" }
+impl Serialize for Eto {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Eto", 4)?;
+        s.serialize_field("kanji", &self.kanji())?;
+        s.serialize_field("stem", self.stem())?;
+        s.serialize_field("branch", self.branch())?;
+        s.serialize_field("animal", self.animal())?;
+        s.end()
+    }
+}
+
+/// Get the 干支 of a year (甲辰 for 2024).
+pub fn year_eto(year: i32) -> Eto {
+    Eto::new(year - YEAR_EPOCH)
+}
+
+/// Get the 干支 of a day (2024-01-01 is 甲子).
+pub fn day_eto(date: NaiveDate) -> Eto {
+    Eto::new(date.num_days_from_ce() + DAY_OFFSET)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn year_eto_of_known_years() {
+        assert_eq!(year_eto(2024).kanji(), "甲辰");
+        assert_eq!(year_eto(2024).animal(), "竜");
+        assert_eq!(year_eto(2025).kanji(), "乙巳");
+        assert_eq!(year_eto(1924).kanji(), "甲子"); // the year 甲子園 opened, named after it
+        assert_eq!(year_eto(1868).kanji(), "戊辰"); // 戊辰戦争
+    }
+
+    #[test]
+    fn day_eto_of_known_days() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(day_eto(date(2024, 1, 1)).kanji(), "甲子");
+        assert_eq!(day_eto(date(2024, 3, 1)).kanji(), "甲子"); // 60 days later
+        assert_eq!(day_eto(date(2024, 1, 2)).kanji(), "乙丑");
+        assert_eq!(day_eto(date(2000, 1, 1)).kanji(), "戊午");
+    }
+}
@@ -0,0 +1,180 @@
+//! # Observance
+//! Days that are observed but are not national holidays, such as Okinawa's 慰霊の日 or the 五節句.
+//!
+//! Observances are opt-in: `compute_holidays` never includes them. When merged they carry
+//! `kind: Observance` and never produce substitute holidays.
+//!
+use std::str::FromStr;
+use anyhow::{anyhow, Context, Error, Result};
+use serde::{Deserialize, Serialize};
+use super::calendar::{compute_holidays, Holiday, HolidayType};
+use super::util::parse_holiday_date;
+
+const REGIONAL_OBSERVANCES: &[u8] = include_bytes!("../resources/regional_observances.csv");
+const SEKKU: &[u8] = include_bytes!("../resources/sekku.csv");
+
+/// Prefecture with a regional observance in `resources/regional_observances.csv`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Prefecture {
+    Okinawa,
+    Tokyo,
+    Saitama,
+    Chiba,
+    Ibaraki,
+    Tochigi,
+    Gunma,
+    Yamanashi,
+    Shizuoka,
+}
+
+const PREFECTURES: [(Prefecture, &str, &str); 9] = [
+    (Prefecture::Okinawa, "okinawa", "沖縄県"),
+    (Prefecture::Tokyo, "tokyo", "東京都"),
+    (Prefecture::Saitama, "saitama", "埼玉県"),
+    (Prefecture::Chiba, "chiba", "千葉県"),
+    (Prefecture::Ibaraki, "ibaraki", "茨城県"),
+    (Prefecture::Tochigi, "tochigi", "栃木県"),
+    (Prefecture::Gunma, "gunma", "群馬県"),
+    (Prefecture::Yamanashi, "yamanashi", "山梨県"),
+    (Prefecture::Shizuoka, "shizuoka", "静岡県"),
+];
+
+impl Prefecture {
+    /// Lowercase romanized name, as used in the CSV ("okinawa").
+    pub fn key(&self) -> &'static str {
+        PREFECTURES.iter().find(|p| p.0 == *self).map(|p| p.1).unwrap_or_default()
+    }
+
+    /// Name in kanji (沖縄県).
+    pub fn kanji(&self) -> &'static str {
+        PREFECTURES.iter().find(|p| p.0 == *self).map(|p| p.2).unwrap_or_default()
+    }
+}
+
+impl FromStr for Prefecture {
+    type Err = Error;
+
+    /// Parse a romanized name case-insensitively ("Okinawa") or a kanji name with or without the suffix (沖縄県, 沖縄).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let key = s.to_lowercase();
+        PREFECTURES.iter()
+            .find(|(_, romaji, kanji)| *romaji == key || *kanji == s || kanji.trim_end_matches(['都', '県']) == s)
+            .map(|p| p.0)
+            .ok_or_else(|| anyhow!("unknown region '{}'", s))
+    }
+}
+
+/// A dataset of observances.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObservanceSet {
+    Regional(Prefecture), // 慰霊の日, 県民の日 and similar prefectural days
+    Sekku, // the five seasonal festivals (五節句), e.g. ひな祭り on 3/3
+}
+
+// A row of regional_observances.csv
+#[derive(Deserialize)]
+struct RegionalRecord {
+    region: String,
+    date: String,
+    name: String,
+}
+
+// A row of sekku.csv
+#[derive(Deserialize)]
+struct SekkuRecord {
+    date: String,
+    name: String,
+}
+
+/// Get the observances of a set in a year, sorted by date.
+pub fn observances(year: u32, set: &ObservanceSet) -> Result<Vec<Holiday>> {
+    let mut days = Vec::new();
+    match set {
+        ObservanceSet::Regional(prefecture) => {
+            let mut reader = csv::Reader::from_reader(REGIONAL_OBSERVANCES);
+            for result in reader.deserialize() {
+                let row: RegionalRecord = result?;
+                if row.region != prefecture.key() {
+                    continue;
+                }
+                let date = parse_holiday_date(&row.date, Some(year))
+                    .with_context(|| format!("row '{}': invalid date '{}'", row.name, row.date))?;
+                days.push(Holiday {
+                    name: row.name,
+                    date,
+                    substitute: false,
+                    kind: HolidayType::Observance,
+                    substitute_for: None,
+                    id: String::new(),
+                    note: None,
+                });
+            }
+        }
+        ObservanceSet::Sekku => {
+            let mut reader = csv::Reader::from_reader(SEKKU);
+            for result in reader.deserialize() {
+                let row: SekkuRecord = result?;
+                let date = parse_holiday_date(&row.date, Some(year))
+                    .with_context(|| format!("row '{}': invalid date '{}'", row.name, row.date))?;
+                days.push(Holiday {
+                    name: row.name,
+                    date,
+                    substitute: false,
+                    kind: HolidayType::Observance,
+                    substitute_for: None,
+                    id: String::new(),
+                    note: None,
+                });
+            }
+        }
+    }
+    days.sort();
+    Ok(days)
+}
+
+/// Get the national holidays of a year merged with the observances of `sets`.
+pub fn compute_holidays_with_observances(year: u32, sets: &[ObservanceSet]) -> Result<Vec<Holiday>> {
+    let mut holidays = compute_holidays(year)?;
+    for set in sets {
+        holidays.extend(observances(year, set)?);
+    }
+    holidays.sort();
+    Ok(holidays)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+    use crate::datebook::calendar::{compute_holidays_with_config, HolidayConfig};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn regional_observances_are_opt_in() {
+        let national = compute_holidays(2024).unwrap();
+        assert!(national.iter().all(|h| h.kind == HolidayType::National));
+        assert_eq!(compute_holidays_with_observances(2024, &[]).unwrap(), national);
+        let config = HolidayConfig { include_observances: true, ..HolidayConfig::default() };
+        let with_sekku = compute_holidays_with_config(2024, &config).unwrap();
+        let tokyo = compute_holidays_with_observances(2024, &[ObservanceSet::Regional(Prefecture::Tokyo)]).unwrap();
+        for holidays in [national, with_sekku, tokyo] {
+            assert!(holidays.iter().all(|h| h.name != "慰霊の日"));
+        }
+    }
+
+    #[test]
+    fn irei_no_hi_in_okinawa() {
+        let holidays = compute_holidays_with_observances(2024, &[ObservanceSet::Regional(Prefecture::Okinawa)]).unwrap();
+        assert_eq!(holidays.len(), compute_holidays(2024).unwrap().len() + 1);
+        let irei: Vec<&Holiday> = holidays.iter().filter(|h| h.kind == HolidayType::Observance).collect();
+        assert_eq!(irei.len(), 1);
+        assert_eq!((irei[0].name.as_str(), irei[0].date, irei[0].substitute), ("慰霊の日", date(2024, 6, 23), false));
+    }
+}
@@ -0,0 +1,1596 @@
+//! # Render
+//! This module serializes a list of holidays into text formats.
+//!
+use std::fmt::{self, Write};
+use std::str::FromStr;
+use anyhow::{anyhow, Result, Error};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::ser::SerializeMap;
+use serde_json::ser::PrettyFormatter;
+use serde::{Deserialize, Serialize, Serializer};
+use super::calendar::{Holiday, HolidayType};
+use super::era::{era_of, wareki};
+use super::rokuyo::rokuyo_for;
+use super::translation::translate;
+use super::util::jp_format::{waso_month_name, weekday_kanji};
+
+/// Output format
+///
+/// Serializes as its lowercase name (`"yaml"`), so it can be read from config files.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Json,
+    JsonWithOptions(JsonOptions),
+    #[serde(alias = "jsonl", alias = "ndjson")]
+    JsonLines, // one compact JSON object per line
+    #[serde(alias = "yml")]
+    Yaml,
+    Csv,
+    CsvWithOptions(CsvOptions),
+    Text, // one `Display` line per holiday
+    JapaneseText, // 令和6年1月1日（月曜日） 元旦
+    Ics, // iCalendar (RFC 5545) with one all-day event per holiday
+    Xml,
+    Toml, // an array of [[holiday]] tables
+    Template(TemplateSpec), // one line per holiday from a `{field}` template
+    Sql { table: String }, // one INSERT statement per holiday
+    #[cfg(feature = "msgpack")]
+    #[serde(alias = "msgpack")]
+    MessagePack, // binary, see `render`
+}
+
+/// Rendered holidays: text for most formats, bytes for binary ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HolidayOutput {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+/// JSON output options. The default matches `OutputFormat::Json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JsonOptions {
+    pub pretty: bool, // `false` writes the whole list on a single line
+    pub indent: JsonIndent, // indentation when `pretty` is set
+}
+
+impl Default for JsonOptions {
+    fn default() -> Self {
+        JsonOptions { pretty: true, indent: JsonIndent::default() }
+    }
+}
+
+/// Indentation of pretty-printed JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonIndent {
+    Spaces(u8),
+    Tabs, // one tab per level
+}
+
+impl Default for JsonIndent {
+    fn default() -> Self {
+        JsonIndent::Spaces(2)
+    }
+}
+
+impl JsonIndent {
+    fn bytes(&self) -> Vec<u8> {
+        match self {
+            JsonIndent::Spaces(n) => vec![b' '; *n as usize],
+            JsonIndent::Tabs => vec![b'\t'],
+        }
+    }
+}
+
+/// How booleans are written in CSV output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvBoolFormat {
+    #[default]
+    TrueFalse,
+    OneZero,
+    YesNo,
+}
+
+impl CsvBoolFormat {
+    fn format(&self, value: bool) -> &'static str {
+        match (self, value) {
+            (CsvBoolFormat::TrueFalse, true) => "true",
+            (CsvBoolFormat::TrueFalse, false) => "false",
+            (CsvBoolFormat::OneZero, true) => "1",
+            (CsvBoolFormat::OneZero, false) => "0",
+            (CsvBoolFormat::YesNo, true) => "yes",
+            (CsvBoolFormat::YesNo, false) => "no",
+        }
+    }
+}
+
+/// Header row of CSV output.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeaderStyle {
+    #[default]
+    Default, // name,date,substitute
+    None, // no header row
+    Japanese, // 名称,日付,振替
+    Custom(Vec<String>), // one label per column
+}
+
+/// A column of the record formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Name,
+    Date,
+    Substitute,
+    Weekday,
+    Era, // 令和
+    EraYear, // 6
+    Wareki, // 令和6年1月1日
+    Kind, // "national", "custom" or "observance"
+    Rokuyo, // 大安
+    SubstituteFor, // date of the original holiday, empty unless substitute
+    Id, // "sports-day", see `Holiday::id`
+    Note, // "Added 2016 by amendment to the Holidays Act", see `Holiday::note`
+    Translated, // whether `name` is in the requested locale, see `RenderOptions::locale`
+}
+
+const DEFAULT_FIELDS: [Field; 3] = [Field::Name, Field::Date, Field::Substitute];
+
+// Added to the default fields of the document formats (JSON, JSON Lines, YAML, MessagePack).
+const DOCUMENT_FIELDS: [Field; 3] = [Field::SubstituteFor, Field::Id, Field::Note];
+
+const WAREKI_FIELDS: [Field; 3] = [Field::Era, Field::EraYear, Field::Wareki];
+
+impl Field {
+    /// Key of the field in JSON and YAML, and its default CSV header label.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Field::Name => "name",
+            Field::Date => "date",
+            Field::Substitute => "substitute",
+            Field::Weekday => "weekday",
+            Field::Era => "era",
+            Field::EraYear => "era_year",
+            Field::Wareki => "wareki",
+            Field::Kind => "kind",
+            Field::Rokuyo => "rokuyo",
+            Field::SubstituteFor => "substitute_for",
+            Field::Id => "id",
+            Field::Note => "note",
+            Field::Translated => "translated",
+        }
+    }
+
+    fn japanese_label(&self) -> &'static str {
+        match self {
+            Field::Name => "名称",
+            Field::Date => "日付",
+            Field::Substitute => "振替",
+            Field::Weekday => "曜日",
+            Field::Era => "元号",
+            Field::EraYear => "和暦年",
+            Field::Wareki => "和暦",
+            Field::Kind => "種別",
+            Field::Rokuyo => "六曜",
+            Field::SubstituteFor => "振替元",
+            Field::Id => "ID",
+            Field::Note => "備考",
+            Field::Translated => "翻訳済み",
+        }
+    }
+
+    fn value(&self, h: &Holiday, options: &RenderOptions) -> Value {
+        match self {
+            Field::Name => Value::Str(options.holiday_name(h)),
+            Field::Date => Value::Str(options.format_date(h.date)),
+            Field::Substitute => Value::Bool(h.substitute),
+            Field::Weekday => Value::Str(options.weekday.unwrap_or_default().format(h.date.weekday()).to_string()),
+            Field::Era => era_of(h.date).map_or(Value::Null, |(era, _)| Value::Str(era.kanji().to_string())),
+            Field::EraYear => era_of(h.date).map_or(Value::Null, |(_, year)| Value::Int(year)),
+            Field::Wareki => Value::Str(wareki(h.date)),
+            Field::Kind => Value::Str(h.kind.key().to_string()),
+            Field::Rokuyo => rokuyo_for(h.date).map_or(Value::Null, |r| Value::Str(r.kanji().to_string())),
+            Field::SubstituteFor => h.substitute_for.map_or(Value::Null, |d| Value::Str(options.format_date(d))),
+            Field::Id => Value::Str(h.id.clone()),
+            Field::Note => h.note.clone().map_or(Value::Null, Value::Str),
+            Field::Translated => Value::Bool(options.locale.as_ref().is_none_or(|l| translate(h, l).translated)),
+        }
+    }
+}
+
+impl FromStr for Field {
+    type Err = Error;
+
+    /// Parse a field by its key ("name", "era_year", ...).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const ALL: [Field; 13] = [
+            Field::Name, Field::Date, Field::Substitute, Field::Weekday, Field::Era,
+            Field::EraYear, Field::Wareki, Field::Kind, Field::Rokuyo, Field::SubstituteFor, Field::Id,
+            Field::Note, Field::Translated,
+        ];
+        let key = s.trim().to_lowercase();
+        ALL.into_iter()
+            .find(|f| f.key() == key)
+            .ok_or_else(|| anyhow!("unknown field '{}'", s))
+    }
+}
+
+impl HeaderStyle {
+    fn labels(&self, fields: &[Field]) -> Result<Option<Vec<String>>, Error> {
+        match self {
+            HeaderStyle::Default => Ok(Some(fields.iter().map(|f| f.key().to_string()).collect())),
+            HeaderStyle::None => Ok(None),
+            HeaderStyle::Japanese => Ok(Some(fields.iter().map(|f| f.japanese_label().to_string()).collect())),
+            HeaderStyle::Custom(labels) => {
+                if labels.len() != fields.len() {
+                    return Err(anyhow!("custom header has {} labels but there are {} columns", labels.len(), fields.len()));
+                }
+                Ok(Some(labels.clone()))
+            }
+        }
+    }
+}
+
+/// CSV output options. The default matches `OutputFormat::Csv`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvOptions {
+    pub bool_format: CsvBoolFormat,
+    pub delimiter: u8,
+    pub include_bom: bool, // prepend a UTF-8 BOM for spreadsheet software
+    pub header: HeaderStyle,
+}
+
+impl CsvOptions {
+    /// Tab-separated values.
+    pub const fn tsv() -> Self {
+        CsvOptions {
+            bool_format: CsvBoolFormat::TrueFalse,
+            delimiter: b'\t',
+            include_bom: false,
+            header: HeaderStyle::Default,
+        }
+    }
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            bool_format: CsvBoolFormat::TrueFalse,
+            delimiter: b',',
+            include_bom: false,
+            header: HeaderStyle::Default,
+        }
+    }
+}
+
+/// Template output: `record` is written once per holiday with `{field}` placeholders
+/// (see `Field::key`) replaced, e.g. `"{date}\t{name}\t{substitute}"`.
+///
+/// `{{` and `}}` write literal braces. `header` and `footer` are written as is.
+/// Every part is followed by a newline.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TemplateSpec {
+    pub record: String,
+    pub header: Option<String>,
+    pub footer: Option<String>,
+}
+
+// A parsed record template.
+enum Segment {
+    Literal(String),
+    Placeholder(Field),
+}
+
+fn parse_template(template: &str) -> Result<Vec<Segment>, Error> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(anyhow!("unclosed placeholder '{{{}' in template", name)),
+                    }
+                }
+                let field = name.parse()
+                    .map_err(|_| anyhow!("unknown placeholder '{{{}}}' in template", name))?;
+                segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                segments.push(Segment::Placeholder(field));
+            }
+            '}' => return Err(anyhow!("unmatched '}}' in template, use '}}}}' for a literal brace")),
+            c => literal.push(c),
+        }
+    }
+    segments.push(Segment::Literal(literal));
+    Ok(segments)
+}
+
+/// Options for the record formats (JSON, JSON Lines, YAML, CSV, TOML and MessagePack).
+/// The default writes the plain `Holiday` fields.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderOptions {
+    pub weekday: Option<WeekdayStyle>, // adds `weekday` after the holiday fields
+    pub include_wareki: bool, // adds `era`, `era_year` and `wareki` (令和6年1月1日) after the holiday fields
+    pub include_rokuyo: bool, // adds `rokuyo` (大安) after the holiday fields
+    pub include_substitute_for: bool, // CSV and TOML: adds a `substitute_for` column, the date a substitute holiday stands in for
+    pub locale: Option<String>, // writes names in this locale ("en") and adds `translated` last, see `translation`
+    pub include_month_name: bool, // JapaneseText: writes the 和風月名 after the month (1月（睦月）)
+    pub date_format: Option<String>, // `NaiveDate::format` string such as "%Y/%m/%d", ISO 8601 when unset
+    pub fields: Option<Vec<Field>>, // exact fields in output order, overriding the `include_*` flags and `weekday`
+}
+
+/// How the `weekday` field is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WeekdayStyle {
+    #[default]
+    English, // "mon"
+    Japanese, // "月"
+}
+
+impl WeekdayStyle {
+    fn format(&self, weekday: Weekday) -> &'static str {
+        match self {
+            WeekdayStyle::English => ENGLISH_WEEKDAYS[weekday.num_days_from_monday() as usize],
+            WeekdayStyle::Japanese => weekday_kanji(weekday),
+        }
+    }
+}
+
+const ENGLISH_WEEKDAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+impl RenderOptions {
+    /// Check that `fields` is a non-empty list without duplicates and that `date_format` can format a date.
+    pub fn validate(&self) -> Result<(), Error> {
+        if let Some(fields) = &self.fields {
+            if fields.is_empty() {
+                return Err(anyhow!("no fields selected"));
+            }
+            if let Some((i, f)) = fields.iter().enumerate().find(|(i, f)| fields[..*i].contains(f)) {
+                return Err(anyhow!("field '{}' is selected twice (position {})", f.key(), i + 1));
+            }
+        }
+        let Some(format) = &self.date_format else {
+            return Ok(());
+        };
+        if let Some(spec) = format_specifiers(format).find(|spec| try_format_date(spec).is_none()) {
+            return Err(anyhow!("invalid date format '{}': unsupported specifier '{}'", format, spec));
+        }
+        if try_format_date(format).is_none() {
+            return Err(anyhow!("invalid date format '{}'", format));
+        }
+        Ok(())
+    }
+
+    // Name of the holiday in `locale`, the Japanese name when unset or untranslated.
+    fn holiday_name(&self, h: &Holiday) -> String {
+        match &self.locale {
+            Some(locale) => translate(h, locale).name,
+            None => h.name.clone(),
+        }
+    }
+
+    fn format_date(&self, date: NaiveDate) -> String {
+        match &self.date_format {
+            Some(format) => date.format(format).to_string(),
+            None => date.to_string(),
+        }
+    }
+
+    /// Fields written for each of `holidays`, in order.
+    ///
+    /// Without an explicit `fields` selection, `kind` is added when a custom holiday
+    /// or an observance is present, so they stay distinguishable from national holidays.
+    pub fn selected_fields(&self, holidays: &[Holiday]) -> Vec<Field> {
+        if let Some(fields) = &self.fields {
+            return fields.clone();
+        }
+        let mut fields = DEFAULT_FIELDS.to_vec();
+        if holidays.iter().any(|h| h.kind != HolidayType::National) {
+            fields.push(Field::Kind);
+        }
+        if self.weekday.is_some() {
+            fields.push(Field::Weekday);
+        }
+        if self.include_wareki {
+            fields.extend(WAREKI_FIELDS);
+        }
+        if self.include_rokuyo {
+            fields.push(Field::Rokuyo);
+        }
+        if self.include_substitute_for {
+            fields.push(Field::SubstituteFor);
+        }
+        if self.locale.is_some() {
+            fields.push(Field::Translated);
+        }
+        fields
+    }
+}
+
+// A single field of a rendered holiday.
+enum Value {
+    Str(String),
+    Bool(bool),
+    Int(u32),
+    Null,
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Str(s) => serializer.serialize_str(s),
+            Value::Bool(b) => serializer.serialize_bool(*b),
+            Value::Int(i) => serializer.serialize_u32(*i),
+            Value::Null => serializer.serialize_none(),
+        }
+    }
+}
+
+// Plain text of a field, as written by the CSV and template formats.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Str(s) => write!(f, "{}", s),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Null => Ok(()),
+        }
+    }
+}
+
+// A holiday as ordered (key, value) pairs, in the order of `RenderOptions::selected_fields`.
+struct Record(Vec<(&'static str, Value)>);
+
+impl Record {
+    // The fields of `omit_null` are left out when they have no value.
+    fn new(h: &Holiday, fields: &[Field], options: &RenderOptions, omit_null: &[Field]) -> Self {
+        Record(fields.iter()
+            .map(|f| (f, f.value(h, options)))
+            .filter(|(f, value)| !(matches!(value, Value::Null) && omit_null.contains(f)))
+            .map(|(f, value)| (f.key(), value))
+            .collect())
+    }
+}
+
+impl Serialize for Record {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in &self.0 {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+// Format a probe date, `None` when the format is malformed or needs a time or offset.
+fn try_format_date(format: &str) -> Option<String> {
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+    if items.contains(&Item::Error) {
+        return None;
+    }
+    let probe = NaiveDate::from_ymd_opt(2024, 1, 1)?;
+    let mut formatted = String::new();
+    write!(formatted, "{}", probe.format_with_items(items.into_iter())).ok()?;
+    Some(formatted)
+}
+
+// The `%` specifiers of a format string, with their padding and width modifiers ("%-d", "%:z").
+fn format_specifiers(format: &str) -> impl Iterator<Item = &str> {
+    format.match_indices('%').filter_map(move |(start, _)| {
+        if format[..start].chars().rev().take_while(|&c| c == '%').count() % 2 == 1 {
+            return None; // the second half of "%%"
+        }
+        let rest = &format[start + 1..];
+        let len = rest.find(|c: char| !matches!(c, '-' | '_' | '0'..='9' | ':' | '.' | '#'))
+            .map(|i| i + rest[i..].chars().next().map_or(0, char::len_utf8))
+            .unwrap_or(rest.len());
+        Some(&format[start..start + 1 + len])
+    })
+}
+
+// How records are laid out: as rows of fixed columns, or as JSON or YAML documents.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Table, // CSV and TOML
+    Json, // JSON, JSON Lines and MessagePack
+    Yaml,
+}
+
+fn records(holidays: &[Holiday], options: &RenderOptions, shape: Shape) -> Vec<Record> {
+    let mut fields = options.selected_fields(holidays);
+    let mut omit_null = Vec::new();
+    if shape != Shape::Table && options.fields.is_none() {
+        // after `kind`, as in `Holiday`; `substitute_for` only on substitute holidays
+        fields.retain(|f| *f != Field::SubstituteFor);
+        let at = fields.iter().position(|f| *f == Field::Kind).unwrap_or(DEFAULT_FIELDS.len() - 1) + 1;
+        fields.splice(at..at, DOCUMENT_FIELDS);
+        omit_null.push(Field::SubstituteFor);
+    }
+    if shape == Shape::Yaml {
+        // JSON writes `"note": null`, YAML leaves the key out
+        omit_null.push(Field::Note);
+    }
+    holidays.iter().map(|h| Record::new(h, &fields, options, &omit_null)).collect()
+}
+
+impl OutputFormat {
+    /// All supported output formats.
+    pub fn all() -> &'static [OutputFormat] {
+        const ALL: &[OutputFormat] = &[
+            OutputFormat::Json,
+            OutputFormat::JsonLines,
+            OutputFormat::Yaml,
+            OutputFormat::Csv,
+            OutputFormat::CsvWithOptions(CsvOptions::tsv()),
+            OutputFormat::Text,
+            OutputFormat::JapaneseText,
+            OutputFormat::Ics,
+            OutputFormat::Xml,
+            OutputFormat::Toml,
+            #[cfg(feature = "msgpack")]
+            OutputFormat::MessagePack,
+        ];
+        ALL
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            OutputFormat::Json => "json",
+            OutputFormat::JsonWithOptions(options) => if options.pretty { "json" } else { "json-compact" },
+            OutputFormat::JsonLines => "jsonl",
+            OutputFormat::Yaml => "yaml",
+            OutputFormat::CsvWithOptions(options) if options.delimiter == b'\t' => "tsv",
+            OutputFormat::Csv | OutputFormat::CsvWithOptions(_) => "csv",
+            OutputFormat::Text => "text",
+            OutputFormat::JapaneseText => "japanesetext",
+            OutputFormat::Ics => "ics",
+            OutputFormat::Xml => "xml",
+            OutputFormat::Toml => "toml",
+            OutputFormat::Template(_) => "template",
+            OutputFormat::Sql { .. } => "sql",
+            #[cfg(feature = "msgpack")]
+            OutputFormat::MessagePack => "msgpack",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Error returned when parsing an unknown format name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFormatError {
+    pub input: String,
+}
+
+impl fmt::Display for ParseFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let supported: Vec<String> = OutputFormat::all().iter().map(|f| f.to_string()).collect();
+        write!(f, "unsupported format '{}' (expected one of: {})", self.input, supported.join(", "))
+    }
+}
+
+impl std::error::Error for ParseFormatError {}
+
+impl FromStr for OutputFormat {
+    type Err = ParseFormatError;
+
+    /// Parse a format name case-insensitively ("json", "json-compact", "jsonl"/"ndjson", "yaml"/"yml", "csv", "tsv", "text"/"txt", "japanesetext"/"ja-text", "ics", "xml", "toml").
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "json" => Ok(OutputFormat::Json),
+            "json-compact" => Ok(OutputFormat::JsonWithOptions(JsonOptions { pretty: false, ..JsonOptions::default() })),
+            "jsonl" | "ndjson" => Ok(OutputFormat::JsonLines),
+            "yaml" | "yml" => Ok(OutputFormat::Yaml),
+            "csv" => Ok(OutputFormat::Csv),
+            "tsv" => Ok(OutputFormat::CsvWithOptions(CsvOptions::tsv())),
+            "text" | "txt" => Ok(OutputFormat::Text),
+            "japanesetext" | "ja-text" => Ok(OutputFormat::JapaneseText),
+            "ics" | "ical" | "icalendar" => Ok(OutputFormat::Ics),
+            "xml" => Ok(OutputFormat::Xml),
+            "toml" => Ok(OutputFormat::Toml),
+            #[cfg(feature = "msgpack")]
+            "msgpack" | "messagepack" => Ok(OutputFormat::MessagePack),
+            _ => Err(ParseFormatError { input: s.to_string() }),
+        }
+    }
+}
+
+/// Serialize a list of holidays in the given format, text or binary.
+pub fn render(holidays: &[Holiday], format: OutputFormat) -> Result<HolidayOutput, Error> {
+    render_with_options(holidays, format, &RenderOptions::default())
+}
+
+/// Like `render`, with extra fields for the record formats.
+pub fn render_with_options(holidays: &[Holiday], format: OutputFormat, options: &RenderOptions) -> Result<HolidayOutput, Error> {
+    match format {
+        #[cfg(feature = "msgpack")]
+        OutputFormat::MessagePack => {
+            options.validate()?;
+            Ok(HolidayOutput::Binary(rmp_serde::to_vec_named(&records(holidays, options, Shape::Json))?))
+        }
+        format => Ok(HolidayOutput::Text(format_holidays_with_options(holidays, format, options)?)),
+    }
+}
+
+/// Serialize a list of holidays to MessagePack.
+///
+/// The payload is the same as `render(.., OutputFormat::MessagePack)`: an array of maps
+/// with the fields of the default JSON output (`date` as a `YYYY-MM-DD` string),
+/// so `rmp_serde::from_slice` reads it back into `Vec<Holiday>`.
+#[cfg(feature = "msgpack")]
+pub fn render_msgpack(holidays: &[Holiday]) -> Result<Vec<u8>, Error> {
+    Ok(rmp_serde::to_vec_named(&records(holidays, &RenderOptions::default(), Shape::Json))?)
+}
+
+/// JSON Schema (draft-07) of the default JSON output: an array of holiday records.
+///
+/// Fields added through `RenderOptions` are not described.
+pub fn holiday_json_schema() -> String {
+    HOLIDAY_JSON_SCHEMA.to_string()
+}
+
+const HOLIDAY_JSON_SCHEMA: &str = r#"{
+  "$schema": "http://json-schema.org/draft-07/schema#",
+  "title": "Holidays",
+  "type": "array",
+  "items": {
+    "title": "Holiday",
+    "type": "object",
+    "properties": {
+      "name": {
+        "type": "string",
+        "description": "Name of the holiday, e.g. 元旦 or 振替休日(建国記念の日)"
+      },
+      "date": {
+        "type": "string",
+        "format": "date",
+        "description": "Date as YYYY-MM-DD"
+      },
+      "substitute": {
+        "type": "boolean",
+        "description": "Whether it is a substitute holiday"
+      },
+      "kind": {
+        "type": "string",
+        "enum": ["national", "custom", "observance"],
+        "description": "Only present when the list has custom holidays or observances, then on every holiday"
+      },
+      "substitute_for": {
+        "type": "string",
+        "format": "date",
+        "description": "Date of the holiday a substitute holiday stands in for, as YYYY-MM-DD; only on substitute holidays"
+      },
+      "id": {
+        "type": "string",
+        "description": "Stable key such as sports-day, substitute:sports-day or custom:創立記念日, empty for observances"
+      },
+      "note": {
+        "type": ["string", "null"],
+        "description": "Background such as the law that added the holiday, null when there is none"
+      }
+    },
+    "required": ["name", "date", "substitute", "id", "note"],
+    "additionalProperties": false
+  }
+}
+"#;
+
+/// Serialize a list of holidays in the given text format.
+pub fn format_holidays(holidays: &[Holiday], format: OutputFormat) -> Result<String, Error> {
+    format_holidays_with_options(holidays, format, &RenderOptions::default())
+}
+
+/// Like `format_holidays`, with extra fields for the record formats.
+///
+/// Every format writes names in `locale`. Otherwise `Text`, `Xml` and `Sql` only use `date_format`,
+/// `Template` uses it and `weekday`, `JapaneseText` only uses `include_month_name`;
+/// `Ics` ignores the other options.
+///
+/// Without an explicit `fields` selection, JSON, JSON Lines and YAML also write `id` and `note`,
+/// and `substitute_for` on substitute holidays, whatever `include_substitute_for` says.
+/// A missing `note` is `null` in JSON and left out in YAML.
+pub fn format_holidays_with_options(holidays: &[Holiday], format: OutputFormat, options: &RenderOptions) -> Result<String, Error> {
+    options.validate()?;
+    match format {
+        OutputFormat::Json => render_json(&records(holidays, options, Shape::Json), &JsonOptions::default()),
+        OutputFormat::JsonWithOptions(json) => render_json(&records(holidays, options, Shape::Json), &json),
+        OutputFormat::JsonLines => render_json_lines(&records(holidays, options, Shape::Json)),
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(&records(holidays, options, Shape::Yaml))?),
+        OutputFormat::Csv => render_csv(holidays, &CsvOptions::default(), options),
+        OutputFormat::CsvWithOptions(csv_options) => render_csv(holidays, &csv_options, options),
+        OutputFormat::Text => Ok(render_text(holidays, options)),
+        OutputFormat::JapaneseText => Ok(render_japanese_text(holidays, options)),
+        OutputFormat::Ics => Ok(render_ics(holidays, options)),
+        OutputFormat::Xml => Ok(render_xml(holidays, options)),
+        OutputFormat::Toml => Ok(toml::to_string(&TomlDocument { holiday: records(holidays, options, Shape::Table) })?),
+        OutputFormat::Template(spec) => render_template(holidays, &spec, options),
+        OutputFormat::Sql { table } => render_sql(holidays, &table, options),
+        #[cfg(feature = "msgpack")]
+        OutputFormat::MessagePack => Err(anyhow!("{} is a binary format, use render() instead", format)),
+    }
+}
+
+// TOML has no top-level arrays, so the list is wrapped in a `holiday` key.
+#[derive(Serialize)]
+struct TomlDocument {
+    holiday: Vec<Record>,
+}
+
+fn render_json(records: &[Record], options: &JsonOptions) -> Result<String, Error> {
+    if options.pretty {
+        let indent = options.indent.bytes();
+        let mut buffer = Vec::new();
+        let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, PrettyFormatter::with_indent(&indent));
+        records.serialize(&mut serializer)?;
+        Ok(String::from_utf8(buffer)?)
+    } else {
+        Ok(serde_json::to_string(records)?)
+    }
+}
+
+fn render_json_lines(records: &[Record]) -> Result<String, Error> {
+    let mut lines = String::new();
+    for r in records {
+        lines.push_str(&serde_json::to_string(r)?);
+        lines.push('\n');
+    }
+    Ok(lines)
+}
+
+fn render_csv(holidays: &[Holiday], csv_options: &CsvOptions, options: &RenderOptions) -> Result<String, Error> {
+    let mut buffer = Vec::new();
+    if csv_options.include_bom {
+        buffer.extend_from_slice("\u{feff}".as_bytes());
+    }
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(csv_options.delimiter)
+        .from_writer(buffer);
+    if let Some(labels) = csv_options.header.labels(&options.selected_fields(holidays))? {
+        writer.write_record(&labels)?;
+    }
+    for record in records(holidays, options, Shape::Table) {
+        writer.write_record(record.0.iter().map(|(_, value)| match value {
+            Value::Bool(b) => csv_options.bool_format.format(*b).to_string(),
+            value => value.to_string(),
+        }))?;
+    }
+    let buffer = writer.into_inner().map_err(|e| anyhow!("failed to write CSV: {}", e))?;
+    Ok(String::from_utf8(buffer)?)
+}
+
+// Same layout as `Holiday`'s `Display`, with the configured date format.
+fn render_text(holidays: &[Holiday], options: &RenderOptions) -> String {
+    holidays.iter()
+        .map(|h| format!("{} ({}) {}{}\n", options.format_date(h.date), weekday_kanji(h.date.weekday()), options.holiday_name(h), kind_marker(h)))
+        .collect()
+}
+
+fn render_template(holidays: &[Holiday], spec: &TemplateSpec, options: &RenderOptions) -> Result<String, Error> {
+    let segments = parse_template(&spec.record)?;
+    let mut text = String::new();
+    if let Some(header) = &spec.header {
+        text.push_str(header);
+        text.push('\n');
+    }
+    for h in holidays {
+        for segment in &segments {
+            match segment {
+                Segment::Literal(s) => text.push_str(s),
+                Segment::Placeholder(field) => text.push_str(&field.value(h, options).to_string()),
+            }
+        }
+        text.push('\n');
+    }
+    if let Some(footer) = &spec.footer {
+        text.push_str(footer);
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+// INSERT INTO jp_holidays (name, date, substitute) VALUES ('元旦', DATE '2024-01-01', FALSE);
+// Dates are `DATE 'YYYY-MM-DD'` literals, or string literals in `date_format` when it is set.
+fn render_sql(holidays: &[Holiday], table: &str, options: &RenderOptions) -> Result<String, Error> {
+    if !is_sql_identifier(table) {
+        return Err(anyhow!("invalid SQL table name '{}'", table));
+    }
+    let date = |date: NaiveDate| match options.date_format {
+        Some(_) => format!("'{}'", options.format_date(date).replace('\'', "''")),
+        None => format!("DATE '{}'", date),
+    };
+    Ok(holidays.iter()
+        .map(|h| format!(
+            "INSERT INTO {} (name, date, substitute) VALUES ('{}', {}, {});\n",
+            table, options.holiday_name(h).replace('\'', "''"), date(h.date), if h.substitute { "TRUE" } else { "FALSE" }
+        ))
+        .collect())
+}
+
+// A plain or schema-qualified identifier such as `jp_holidays` or `public.jp_holidays`.
+fn is_sql_identifier(name: &str) -> bool {
+    let parts: Vec<&str> = name.split('.').collect();
+    parts.len() <= 2 && parts.iter().all(|part| {
+        part.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+fn render_japanese_text(holidays: &[Holiday], options: &RenderOptions) -> String {
+    holidays.iter()
+        .map(|h| format!("{}（{}曜日） {}{}\n", japanese_date(h.date, options), weekday_kanji(h.date.weekday()), options.holiday_name(h), kind_marker(h)))
+        .collect()
+}
+
+// 令和6年1月1日, or 令和6年1月（睦月）1日 with `include_month_name`.
+fn japanese_date(date: NaiveDate, options: &RenderOptions) -> String {
+    let date_text = wareki(date);
+    match waso_month_name(date.month()).filter(|_| options.include_month_name) {
+        // era names contain no 月, so the first one follows the month number
+        Some(name) => date_text.replacen('月', &format!("月（{}）", name), 1),
+        None => date_text,
+    }
+}
+
+// " [行事]" for custom holidays and observances, empty for national holidays.
+fn kind_marker(h: &Holiday) -> String {
+    match h.kind {
+        HolidayType::National => String::new(),
+        kind => format!(" [{}]", kind.kanji()),
+    }
+}
+
+// DTSTAMP of every event: a fixed time rather than the time of rendering, so that the
+// output of a year is reproducible.
+const ICS_DTSTAMP: &str = "20240101T000000Z";
+
+fn render_ics(holidays: &[Holiday], options: &RenderOptions) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//jpn_holidays_wasm//Japanese holidays//JA".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for h in holidays {
+        let start = h.date.format("%Y%m%d");
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}-{}@jpn_holidays_wasm", start, ics_uid_key(h)));
+        lines.push(format!("DTSTAMP:{}", ICS_DTSTAMP));
+        lines.push(format!("DTSTART;VALUE=DATE:{}", start));
+        lines.push(format!("DTEND;VALUE=DATE:{}", (h.date + Duration::days(1)).format("%Y%m%d")));
+        lines.push(format!("SUMMARY:{}", escape_ics_text(&options.holiday_name(h))));
+        lines.push(format!("CATEGORIES:{}", if h.substitute { "振替休日" } else { h.kind.kanji() }));
+        lines.push("TRANSP:TRANSPARENT".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|l| fold_ics_line(l) + "\r\n").collect()
+}
+
+// Part of the UID that tells apart holidays on the same date: the id, or a hash of the
+// name for observances and custom holidays, whose ids are empty or not ASCII.
+fn ics_uid_key(h: &Holiday) -> String {
+    if !h.id.is_empty() && h.id.is_ascii() {
+        return h.id.replace(':', "-");
+    }
+    // 32-bit FNV-1a, stable across builds unlike `DefaultHasher`
+    let hash = h.name.bytes().fold(0x811c9dc5u32, |hash, b| (hash ^ b as u32).wrapping_mul(0x01000193));
+    format!("{:08x}", hash)
+}
+
+fn escape_ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// Lines longer than 75 octets are folded with CRLF followed by a space.
+fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+// <holidays year="2024"><holiday substitute="false"><name>元旦</name><date>2024-01-01</date></holiday>...</holidays>
+// The year attribute is only written when every holiday falls in the same year.
+fn render_xml(holidays: &[Holiday], options: &RenderOptions) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let year = holidays.first().map(|h| h.date.year());
+    match year {
+        Some(y) if holidays.iter().all(|h| h.date.year() == y) => {
+            xml.push_str(&format!("<holidays year=\"{}\">\n", y));
+        }
+        _ => xml.push_str("<holidays>\n"),
+    }
+    for h in holidays {
+        xml.push_str(&format!(
+            "  <holiday substitute=\"{}\"{}><name>{}</name><date>{}</date></holiday>\n",
+            h.substitute,
+            if h.kind == HolidayType::National { String::new() } else { format!(" kind=\"{}\"", h.kind.key()) },
+            escape_xml(&options.holiday_name(h)),
+            escape_xml(&options.format_date(h.date))
+        ));
+    }
+    xml.push_str("</holidays>\n");
+    xml
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use crate::datebook::calendar::{compute_holidays, compute_holidays_with_warnings, CustomHolidays, HolidayCalendar};
+    use crate::datebook::observance::{compute_holidays_with_observances, ObservanceSet};
+
+    #[test]
+    fn ics_round_trips_through_an_icalendar_parser() {
+        // 端午の節句 falls on こどもの日, so two events share a date
+        let holidays = compute_holidays_with_observances(2024, &[ObservanceSet::Sekku]).unwrap();
+        let ics = format_holidays(&holidays, OutputFormat::Ics).unwrap();
+        let calendar = ical::IcalParser::new(ics.as_bytes()).next().unwrap().unwrap();
+        assert_eq!(calendar.events.len(), holidays.len());
+
+        let mut uids = std::collections::HashSet::new();
+        for (event, h) in calendar.events.iter().zip(&holidays) {
+            let property = |name: &str| event.properties.iter()
+                .find(|p| p.name == name)
+                .and_then(|p| p.value.clone())
+                .unwrap();
+            assert!(uids.insert(property("UID")), "duplicate UID for {}", h.name);
+            assert_eq!(property("DTSTAMP"), ICS_DTSTAMP);
+            assert_eq!(property("DTSTART"), h.date.format("%Y%m%d").to_string());
+            assert_eq!(property("SUMMARY"), h.name);
+        }
+    }
+
+    #[test]
+    fn ics_uids_are_built_from_the_holiday_id() {
+        let holidays = compute_holidays(2024).unwrap();
+        let ics = format_holidays(&holidays, OutputFormat::Ics).unwrap();
+        assert!(ics.contains("UID:20240101-new-years-day@jpn_holidays_wasm\r\n"));
+        assert!(ics.contains("UID:20240212-substitute-national-foundation-day@jpn_holidays_wasm\r\n"));
+    }
+
+    #[test]
+    fn sql_for_2024() {
+        let holidays = compute_holidays(2024).unwrap();
+        let sql = format_holidays(&holidays, OutputFormat::Sql { table: "jp_holidays".to_string() }).unwrap();
+        assert_eq!(sql, SQL_2024);
+    }
+
+    #[test]
+    fn sql_writes_dates_in_date_format() {
+        let holidays = compute_holidays(2024).unwrap();
+        let options = RenderOptions { date_format: Some("%Y/%m/%d".to_string()), ..RenderOptions::default() };
+        let sql = format_holidays_with_options(&holidays[..1], OutputFormat::Sql { table: "jp_holidays".to_string() }, &options).unwrap();
+        assert_eq!(sql, "INSERT INTO jp_holidays (name, date, substitute) VALUES ('元旦', '2024/01/01', FALSE);\n");
+    }
+
+    const SQL_2024: &str = "\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('元旦', DATE '2024-01-01', FALSE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('成人の日', DATE '2024-01-08', FALSE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('建国記念の日', DATE '2024-02-11', FALSE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('振替休日(建国記念の日)', DATE '2024-02-12', TRUE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('天皇誕生日', DATE '2024-02-23', FALSE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('春分の日', DATE '2024-03-20', FALSE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('昭和の日', DATE '2024-04-29', FALSE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('憲法記念日', DATE '2024-05-03', FALSE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('みどりの日', DATE '2024-05-04', FALSE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('こどもの日', DATE '2024-05-05', FALSE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('振替休日(こどもの日)', DATE '2024-05-06', TRUE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('海の日', DATE '2024-07-15', FALSE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('山の日', DATE '2024-08-11', FALSE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('振替休日(山の日)', DATE '2024-08-12', TRUE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('敬老の日', DATE '2024-09-16', FALSE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('秋分の日', DATE '2024-09-22', FALSE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('振替休日(秋分の日)', DATE '2024-09-23', TRUE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('スポーツの日', DATE '2024-10-14', FALSE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('文化の日', DATE '2024-11-03', FALSE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('振替休日(文化の日)', DATE '2024-11-04', TRUE);\n\
+INSERT INTO jp_holidays (name, date, substitute) VALUES ('勤労感謝の日', DATE '2024-11-23', FALSE);\n\
+";
+
+    // Names and dates are those of the first release, from before relative dates were
+    // computed without the local timezone; `id` was added since.
+    #[test]
+    fn json_for_2024() {
+        let holidays = compute_holidays(2024).unwrap();
+        assert_eq!(format_holidays(&holidays, OutputFormat::Json).unwrap(), JSON_2024);
+    }
+
+    #[test]
+    fn yaml_for_new_years_day() {
+        let holidays = compute_holidays(2024).unwrap();
+        assert_eq!(format_holidays(&holidays[..1], OutputFormat::Yaml).unwrap(), "\
+- name: 元旦
+  date: 2024-01-01
+  substitute: false
+  id: new-years-day
+");
+    }
+
+    // Checks the subset of JSON Schema used by `HOLIDAY_JSON_SCHEMA`.
+    fn validate(value: &serde_json::Value, schema: &serde_json::Value, path: &str) {
+        use serde_json::Value;
+        match &schema["type"] {
+            Value::String(t) => assert!(has_type(value, t), "{}: not a {}", path, t),
+            Value::Array(types) => assert!(types.iter().any(|t| has_type(value, t.as_str().unwrap())), "{}: {}", path, value),
+            _ => {}
+        }
+        if let Some(allowed) = schema["enum"].as_array() {
+            assert!(allowed.contains(value), "{}: {} is not allowed", path, value);
+        }
+        if schema["format"] == "date" {
+            assert!(NaiveDate::parse_from_str(value.as_str().unwrap(), "%Y-%m-%d").is_ok(), "{}: {}", path, value);
+        }
+        if let Some(items) = value.as_array() {
+            for (i, item) in items.iter().enumerate() {
+                validate(item, &schema["items"], &format!("{}[{}]", path, i));
+            }
+        }
+        if let Some(object) = value.as_object() {
+            for key in schema["required"].as_array().unwrap() {
+                assert!(object.contains_key(key.as_str().unwrap()), "{}: missing {}", path, key);
+            }
+            for (key, field) in object {
+                let property = &schema["properties"][key];
+                assert!(!property.is_null(), "{}: unexpected {}", path, key);
+                validate(field, property, &format!("{}.{}", path, key));
+            }
+        }
+    }
+
+    fn has_type(value: &serde_json::Value, t: &str) -> bool {
+        match t {
+            "array" => value.is_array(),
+            "object" => value.is_object(),
+            "string" => value.is_string(),
+            "boolean" => value.is_boolean(),
+            "null" => value.is_null(),
+            _ => panic!("unexpected type {}", t),
+        }
+    }
+
+    #[test]
+    fn json_output_matches_the_schema() {
+        let schema: serde_json::Value = serde_json::from_str(&holiday_json_schema()).unwrap();
+        let custom = CustomHolidays::new().annual("創立記念日", 9, 2);
+        for holidays in [
+            compute_holidays(2024).unwrap(),
+            golden_week_2019(),
+            HolidayCalendar::with_custom(2024, &custom).unwrap().holidays().to_vec(),
+            compute_holidays_with_observances(2024, &[ObservanceSet::Sekku]).unwrap(),
+        ] {
+            let json: serde_json::Value = serde_json::from_str(&format_holidays(&holidays, OutputFormat::Json).unwrap()).unwrap();
+            validate(&json, &schema, "$");
+        }
+    }
+
+    #[test]
+    fn missing_note_is_null_in_json_and_left_out_in_yaml() {
+        let holidays = compute_holidays(2024).unwrap();
+        let (new_year, coming_of_age) = (&holidays[..1], &holidays[1..2]);
+        assert_eq!(format_holidays(coming_of_age, OutputFormat::Yaml).unwrap(), "\
+- name: 成人の日
+  date: 2024-01-08
+  substitute: false
+  id: coming-of-age-day
+  note: Second Monday of January since 2000
+");
+        assert!(format_holidays(new_year, OutputFormat::Json).unwrap().contains("\"note\": null"));
+
+        let options = RenderOptions { fields: Some(vec![Field::Name, Field::Note]), ..RenderOptions::default() };
+        let json = format_holidays_with_options(new_year, OutputFormat::JsonLines, &options).unwrap();
+        assert_eq!(json, "{\"name\":\"元旦\",\"note\":null}\n");
+        let yaml = format_holidays_with_options(new_year, OutputFormat::Yaml, &options).unwrap();
+        assert_eq!(yaml, "- name: 元旦\n");
+        let yaml = format_holidays_with_options(coming_of_age, OutputFormat::Yaml, &options).unwrap();
+        assert_eq!(yaml, "- name: 成人の日\n  note: Second Monday of January since 2000\n");
+    }
+
+    #[test]
+    fn substitute_for_is_written_for_substitutes_whatever_the_option() {
+        let holidays = compute_holidays(2024).unwrap();
+        let substitute: Vec<Holiday> = holidays.iter().filter(|h| h.substitute && h.date.month() == 2).cloned().collect();
+        let yaml = "\
+- name: 振替休日(建国記念の日)
+  date: 2024-02-12
+  substitute: true
+  substitute_for: 2024-02-11
+  id: substitute:national-foundation-day
+";
+        assert_eq!(format_holidays(&substitute, OutputFormat::Yaml).unwrap(), yaml);
+        let options = RenderOptions { include_substitute_for: true, ..RenderOptions::default() };
+        assert_eq!(format_holidays_with_options(&substitute, OutputFormat::Yaml, &options).unwrap(), yaml);
+        let json = format_holidays_with_options(&holidays, OutputFormat::Json, &options).unwrap();
+        assert_eq!(json, format_holidays(&holidays, OutputFormat::Json).unwrap());
+        assert!(format_holidays(&holidays, OutputFormat::Csv).unwrap().starts_with("name,date,substitute\n"));
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn msgpack_round_trips() {
+        let holidays = compute_holidays(2024).unwrap();
+        let bytes = render_msgpack(&holidays).unwrap();
+        assert_eq!(rmp_serde::from_slice::<Vec<Holiday>>(&bytes).unwrap(), holidays);
+
+        let HolidayOutput::Binary(rendered) = render(&holidays, OutputFormat::MessagePack).unwrap() else {
+            panic!("MessagePack is a binary format");
+        };
+        assert_eq!(rendered, bytes);
+
+        let custom = CustomHolidays::new().annual("創立記念日", 9, 2);
+        let holidays = HolidayCalendar::with_custom(2024, &custom).unwrap().holidays().to_vec();
+        let bytes = render_msgpack(&holidays).unwrap();
+        assert_eq!(rmp_serde::from_slice::<Vec<Holiday>>(&bytes).unwrap(), holidays);
+    }
+
+    #[test]
+    fn csv_bool_formats() {
+        let holidays = compute_holidays(2024).unwrap();
+        let foundation_day = &holidays[2..4];
+        for (bool_format, no, yes) in [
+            (CsvBoolFormat::TrueFalse, "false", "true"),
+            (CsvBoolFormat::OneZero, "0", "1"),
+            (CsvBoolFormat::YesNo, "no", "yes"),
+        ] {
+            let options = CsvOptions { bool_format, ..CsvOptions::default() };
+            let csv = format_holidays(foundation_day, OutputFormat::CsvWithOptions(options)).unwrap();
+            assert_eq!(csv, format!("name,date,substitute\n建国記念の日,2024-02-11,{}\n振替休日(建国記念の日),2024-02-12,{}\n", no, yes));
+        }
+    }
+
+    #[test]
+    fn csv_quotes_names_with_commas() {
+        let custom = CustomHolidays::new().annual("創立記念日, \"本社\"", 9, 2);
+        let calendar = HolidayCalendar::with_custom(2024, &custom).unwrap();
+        let founding_day: Vec<Holiday> = calendar.holidays().iter().filter(|h| h.kind == HolidayType::Custom).cloned().collect();
+        let csv = format_holidays(&founding_day, OutputFormat::Csv).unwrap();
+        assert_eq!(csv, "name,date,substitute,kind\n\"創立記念日, \"\"本社\"\"\",2024-09-02,false,custom\n");
+    }
+
+    #[test]
+    fn xml_escapes_names() {
+        let custom = CustomHolidays::new().annual("R&D <休業>", 9, 2);
+        let calendar = HolidayCalendar::with_custom(2024, &custom).unwrap();
+        let holidays: Vec<Holiday> = calendar.holidays().iter().filter(|h| h.kind == HolidayType::Custom || h.name == "文化の日").cloned().collect();
+        assert_eq!(format_holidays(&holidays, OutputFormat::Xml).unwrap(), "\
+<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<holidays year=\"2024\">
+  <holiday substitute=\"false\" kind=\"custom\"><name>R&amp;D &lt;休業&gt;</name><date>2024-09-02</date></holiday>
+  <holiday substitute=\"false\"><name>文化の日</name><date>2024-11-03</date></holiday>
+</holidays>
+");
+
+        let custom = CustomHolidays::new().annual("R&D <休業>", 9, 2).annual("\"創立\" & 'R&D' > 休業", 9, 3);
+        let holidays = HolidayCalendar::with_custom(2024, &custom).unwrap().holidays().to_vec();
+        let elements = read_xml(&format_holidays(&holidays, OutputFormat::Xml).unwrap());
+        let names: Vec<&str> = elements.iter().filter(|(tag, _)| tag == "name").map(|(_, text)| text.as_str()).collect();
+        assert_eq!(names, holidays.iter().map(|h| h.name.as_str()).collect::<Vec<_>>());
+        assert!(names.contains(&"\"創立\" & 'R&D' > 休業"));
+    }
+
+    // A minimal reader for the output of `render_xml`: checks that the tags nest and that text
+    // only uses the predefined entities, and returns the tag and unescaped text of each element.
+    fn read_xml(xml: &str) -> Vec<(String, String)> {
+        let mut rest = xml.strip_prefix("<?xml version=\"1.0\" encoding=\"UTF-8\"?>").expect("missing XML declaration");
+        let (mut open, mut elements, mut text) = (Vec::new(), Vec::new(), String::new());
+        while let Some(start) = rest.find(['<', '&']) {
+            text.push_str(&rest[..start]);
+            rest = &rest[start..];
+            if rest.starts_with('&') {
+                let end = rest.find(';').expect("unterminated entity");
+                text.push(match &rest[1..end] {
+                    "amp" => '&',
+                    "lt" => '<',
+                    "gt" => '>',
+                    "quot" => '"',
+                    "apos" => '\'',
+                    entity => panic!("unknown entity &{};", entity),
+                });
+                rest = &rest[end + 1..];
+                continue;
+            }
+            let end = rest.find('>').expect("unterminated tag");
+            let tag = &rest[1..end];
+            if let Some(name) = tag.strip_prefix('/') {
+                assert_eq!(open.pop().as_deref(), Some(name), "unexpected </{}>", name);
+                elements.push((name.to_string(), std::mem::take(&mut text)));
+            } else {
+                let (name, attributes) = tag.split_once(' ').unwrap_or((tag, ""));
+                assert!(!attributes.contains('<') && attributes.matches('"').count() % 2 == 0, "malformed <{}>", tag);
+                open.push(name.to_string());
+                text.clear();
+            }
+            rest = &rest[end + 1..];
+        }
+        assert!(open.is_empty() && rest.trim().is_empty(), "unclosed {:?}", open);
+        elements
+    }
+
+    #[test]
+    fn xml_omits_the_year_across_years() {
+        let mut holidays = compute_holidays(2024).unwrap()[..1].to_vec();
+        holidays.extend_from_slice(&compute_holidays(2025).unwrap()[..1]);
+        let xml = format_holidays(&holidays, OutputFormat::Xml).unwrap();
+        assert!(xml.contains("\n<holidays>\n") && xml.ends_with("</holidays>\n"), "{}", xml);
+    }
+
+    #[test]
+    fn output_formats_round_trip_through_json() {
+        let mut formats = OutputFormat::all().to_vec();
+        formats.push(OutputFormat::JsonWithOptions(JsonOptions { pretty: false, ..JsonOptions::default() }));
+        formats.push(OutputFormat::Template(TemplateSpec { record: "{date} {name}".to_string(), ..TemplateSpec::default() }));
+        formats.push(OutputFormat::Sql { table: "jp_holidays".to_string() });
+        for format in formats {
+            let json = serde_json::to_string(&format).unwrap();
+            assert_eq!(serde_json::from_str::<OutputFormat>(&json).unwrap(), format, "{}", json);
+        }
+        assert_eq!(serde_json::from_str::<OutputFormat>("\"yml\"").unwrap(), OutputFormat::Yaml);
+        assert_eq!(serde_json::to_string(&OutputFormat::JapaneseText).unwrap(), "\"japanesetext\"");
+    }
+
+    #[test]
+    fn format_names_parse_back() {
+        for format in OutputFormat::all() {
+            assert_eq!(&format.to_string().parse::<OutputFormat>().unwrap(), format);
+        }
+        let err = "pdf".parse::<OutputFormat>().unwrap_err();
+        assert_eq!(err, ParseFormatError { input: "pdf".to_string() });
+        assert!(err.to_string().starts_with("unsupported format 'pdf' (expected one of: json, jsonl, yaml, csv, tsv,"), "{}", err);
+        assert!(serde_json::from_str::<OutputFormat>("\"pdf\"").is_err());
+    }
+
+    // Golden Week 2019 around the change from 平成 to 令和; 2019 has no equinox data, hence the warning.
+    fn golden_week_2019() -> Vec<Holiday> {
+        let (holidays, warnings) = compute_holidays_with_warnings(2019);
+        assert_eq!(warnings.len(), 1);
+        let (from, until) = (NaiveDate::from_ymd_opt(2019, 4, 29).unwrap(), NaiveDate::from_ymd_opt(2019, 5, 2).unwrap());
+        holidays.into_iter().filter(|h| (from..=until).contains(&h.date)).collect()
+    }
+
+    #[test]
+    fn japanese_text_across_the_reiwa_boundary() {
+        assert_eq!(format_holidays(&golden_week_2019(), OutputFormat::JapaneseText).unwrap(), "\
+平成31年4月29日（月曜日） 昭和の日
+平成31年4月30日（火曜日） 国民の休日
+令和元年5月1日（水曜日） 天皇の即位の日
+令和元年5月2日（木曜日） 国民の休日
+");
+    }
+
+    #[test]
+    fn wareki_fields_across_the_reiwa_boundary() {
+        let options = RenderOptions { include_wareki: true, ..RenderOptions::default() };
+        let lines = format_holidays_with_options(&golden_week_2019()[1..3], OutputFormat::JsonLines, &options).unwrap();
+        assert_eq!(lines, "\
+{\"name\":\"国民の休日\",\"date\":\"2019-04-30\",\"substitute\":false,\"id\":\"citizens-holiday\",\"note\":null,\"era\":\"平成\",\"era_year\":31,\"wareki\":\"平成31年4月30日\"}
+{\"name\":\"天皇の即位の日\",\"date\":\"2019-05-01\",\"substitute\":false,\"id\":\"enthronement-day\",\"note\":null,\"era\":\"令和\",\"era_year\":1,\"wareki\":\"令和元年5月1日\"}
+");
+    }
+
+    #[test]
+    fn weekday_field() {
+        let holidays = compute_holidays(2024).unwrap();
+        let foundation_day = &holidays[2..4];
+        let csv = |weekday| {
+            let options = RenderOptions { weekday: Some(weekday), ..RenderOptions::default() };
+            format_holidays_with_options(foundation_day, OutputFormat::Csv, &options).unwrap()
+        };
+        assert_eq!(csv(WeekdayStyle::English), "\
+name,date,substitute,weekday
+建国記念の日,2024-02-11,false,sun
+振替休日(建国記念の日),2024-02-12,true,mon
+");
+        assert_eq!(csv(WeekdayStyle::Japanese), "\
+name,date,substitute,weekday
+建国記念の日,2024-02-11,false,日
+振替休日(建国記念の日),2024-02-12,true,月
+");
+    }
+
+    const JSON_2024: &str = r#"[
+  {
+    "name": "元旦",
+    "date": "2024-01-01",
+    "substitute": false,
+    "id": "new-years-day",
+    "note": null
+  },
+  {
+    "name": "成人の日",
+    "date": "2024-01-08",
+    "substitute": false,
+    "id": "coming-of-age-day",
+    "note": "Second Monday of January since 2000"
+  },
+  {
+    "name": "建国記念の日",
+    "date": "2024-02-11",
+    "substitute": false,
+    "id": "national-foundation-day",
+    "note": "Added 1966 by amendment to the Holidays Act"
+  },
+  {
+    "name": "振替休日(建国記念の日)",
+    "date": "2024-02-12",
+    "substitute": true,
+    "substitute_for": "2024-02-11",
+    "id": "substitute:national-foundation-day",
+    "note": null
+  },
+  {
+    "name": "天皇誕生日",
+    "date": "2024-02-23",
+    "substitute": false,
+    "id": "emperors-birthday",
+    "note": "Birthday of the current Emperor since 2020"
+  },
+  {
+    "name": "春分の日",
+    "date": "2024-03-20",
+    "substitute": false,
+    "id": "vernal-equinox-day",
+    "note": null
+  },
+  {
+    "name": "昭和の日",
+    "date": "2024-04-29",
+    "substitute": false,
+    "id": "showa-day",
+    "note": "Renamed from みどりの日 in 2007"
+  },
+  {
+    "name": "憲法記念日",
+    "date": "2024-05-03",
+    "substitute": false,
+    "id": "constitution-memorial-day",
+    "note": null
+  },
+  {
+    "name": "みどりの日",
+    "date": "2024-05-04",
+    "substitute": false,
+    "id": "greenery-day",
+    "note": "Moved from 4/29 in 2007"
+  },
+  {
+    "name": "こどもの日",
+    "date": "2024-05-05",
+    "substitute": false,
+    "id": "childrens-day",
+    "note": null
+  },
+  {
+    "name": "振替休日(こどもの日)",
+    "date": "2024-05-06",
+    "substitute": true,
+    "substitute_for": "2024-05-05",
+    "id": "substitute:childrens-day",
+    "note": null
+  },
+  {
+    "name": "海の日",
+    "date": "2024-07-15",
+    "substitute": false,
+    "id": "marine-day",
+    "note": "Third Monday of July since 2003"
+  },
+  {
+    "name": "山の日",
+    "date": "2024-08-11",
+    "substitute": false,
+    "id": "mountain-day",
+    "note": "Added 2016 by amendment to the Holidays Act"
+  },
+  {
+    "name": "振替休日(山の日)",
+    "date": "2024-08-12",
+    "substitute": true,
+    "substitute_for": "2024-08-11",
+    "id": "substitute:mountain-day",
+    "note": null
+  },
+  {
+    "name": "敬老の日",
+    "date": "2024-09-16",
+    "substitute": false,
+    "id": "respect-for-the-aged-day",
+    "note": "Third Monday of September since 2003"
+  },
+  {
+    "name": "秋分の日",
+    "date": "2024-09-22",
+    "substitute": false,
+    "id": "autumnal-equinox-day",
+    "note": null
+  },
+  {
+    "name": "振替休日(秋分の日)",
+    "date": "2024-09-23",
+    "substitute": true,
+    "substitute_for": "2024-09-22",
+    "id": "substitute:autumnal-equinox-day",
+    "note": null
+  },
+  {
+    "name": "スポーツの日",
+    "date": "2024-10-14",
+    "substitute": false,
+    "id": "sports-day",
+    "note": "Renamed from 体育の日 in 2020"
+  },
+  {
+    "name": "文化の日",
+    "date": "2024-11-03",
+    "substitute": false,
+    "id": "culture-day",
+    "note": null
+  },
+  {
+    "name": "振替休日(文化の日)",
+    "date": "2024-11-04",
+    "substitute": true,
+    "substitute_for": "2024-11-03",
+    "id": "substitute:culture-day",
+    "note": null
+  },
+  {
+    "name": "勤労感謝の日",
+    "date": "2024-11-23",
+    "substitute": false,
+    "id": "labor-thanksgiving-day",
+    "note": null
+  }
+]"#;
+
+    #[test]
+    fn toml_for_2024() {
+        let holidays = compute_holidays(2024).unwrap();
+        assert_eq!(format_holidays(&holidays, OutputFormat::Toml).unwrap(), TOML_2024);
+    }
+
+    const TOML_2024: &str = r#"[[holiday]]
+name = "元旦"
+date = "2024-01-01"
+substitute = false
+
+[[holiday]]
+name = "成人の日"
+date = "2024-01-08"
+substitute = false
+
+[[holiday]]
+name = "建国記念の日"
+date = "2024-02-11"
+substitute = false
+
+[[holiday]]
+name = "振替休日(建国記念の日)"
+date = "2024-02-12"
+substitute = true
+
+[[holiday]]
+name = "天皇誕生日"
+date = "2024-02-23"
+substitute = false
+
+[[holiday]]
+name = "春分の日"
+date = "2024-03-20"
+substitute = false
+
+[[holiday]]
+name = "昭和の日"
+date = "2024-04-29"
+substitute = false
+
+[[holiday]]
+name = "憲法記念日"
+date = "2024-05-03"
+substitute = false
+
+[[holiday]]
+name = "みどりの日"
+date = "2024-05-04"
+substitute = false
+
+[[holiday]]
+name = "こどもの日"
+date = "2024-05-05"
+substitute = false
+
+[[holiday]]
+name = "振替休日(こどもの日)"
+date = "2024-05-06"
+substitute = true
+
+[[holiday]]
+name = "海の日"
+date = "2024-07-15"
+substitute = false
+
+[[holiday]]
+name = "山の日"
+date = "2024-08-11"
+substitute = false
+
+[[holiday]]
+name = "振替休日(山の日)"
+date = "2024-08-12"
+substitute = true
+
+[[holiday]]
+name = "敬老の日"
+date = "2024-09-16"
+substitute = false
+
+[[holiday]]
+name = "秋分の日"
+date = "2024-09-22"
+substitute = false
+
+[[holiday]]
+name = "振替休日(秋分の日)"
+date = "2024-09-23"
+substitute = true
+
+[[holiday]]
+name = "スポーツの日"
+date = "2024-10-14"
+substitute = false
+
+[[holiday]]
+name = "文化の日"
+date = "2024-11-03"
+substitute = false
+
+[[holiday]]
+name = "振替休日(文化の日)"
+date = "2024-11-04"
+substitute = true
+
+[[holiday]]
+name = "勤労感謝の日"
+date = "2024-11-23"
+substitute = false
+"#;
+}
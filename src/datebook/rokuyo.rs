@@ -0,0 +1,121 @@
+//! # Rokuyo
+//! The six-day cycle (六曜) printed on Japanese calendars, e.g. 2024-01-01 is 赤口.
+//!
+//! The 六曜 follows the old lunisolar calendar: 先勝 falls on the first day of
+//! months 1 and 7, 友引 on that of months 2 and 8, and so on, then the cycle
+//! repeats daily. Lunar months start on the day of the new moon (JST) and are
+//! numbered by the 中気 they contain; a month without one is a leap month that
+//! repeats the previous number.
+//!
+use std::fmt;
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use super::astro;
+use super::sekki::SUPPORTED_YEARS;
+
+/// 六曜
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rokuyo {
+    Taian, // 大安
+    Shakko, // 赤口
+    Sensho, // 先勝
+    Tomobiki, // 友引
+    Senbu, // 先負
+    Butsumetsu, // 仏滅
+}
+
+// Indexed by (lunar month + lunar day) % 6
+const CYCLE: [Rokuyo; 6] = [Rokuyo::Taian, Rokuyo::Shakko, Rokuyo::Sensho, Rokuyo::Tomobiki, Rokuyo::Senbu, Rokuyo::Butsumetsu];
+
+impl Rokuyo {
+    /// Name in kanji (大安).
+    pub fn kanji(&self) -> &'static str {
+        match self {
+            Rokuyo::Taian => "大安",
+            Rokuyo::Shakko => "赤口",
+            Rokuyo::Sensho => "先勝",
+            Rokuyo::Tomobiki => "友引",
+            Rokuyo::Senbu => "先負",
+            Rokuyo::Butsumetsu => "仏滅",
+        }
+    }
+}
+
+impl fmt::Display for Rokuyo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.kanji())
+    }
+}
+
+/// Get the 六曜 of a date between 1900 and 2100.
+pub fn rokuyo_for(date: NaiveDate) -> Result<Rokuyo> {
+    let (month, day) = lunar_month_day(date)?;
+    Ok(CYCLE[((month + day) % 6) as usize])
+}
+
+// Lunar month number (1-12) and day of a date.
+fn lunar_month_day(date: NaiveDate) -> Result<(u32, u32)> {
+    if !u32::try_from(date.year()).is_ok_and(|y| SUPPORTED_YEARS.contains(&y)) {
+        return Err(anyhow!("rokuyo is only available for {}-{}", SUPPORTED_YEARS.start(), SUPPORTED_YEARS.end()));
+    }
+    let next_day = date + Duration::days(1);
+    let k = astro::lunation_before(jst_midnight(next_day));
+    let start = astro::jst_date(astro::new_moon(k));
+    let day = (date - start).num_days() as u32 + 1;
+
+    // a leap month takes the number of the month before it
+    let month = (0..3)
+        .find_map(|back| month_number(k - back))
+        .ok_or_else(|| anyhow!("no 中気 found near {}", date))?;
+    Ok((month, day))
+}
+
+// Number of the lunar month starting at the `k`-th new moon, `None` for a leap month.
+fn month_number(k: i64) -> Option<u32> {
+    let start = astro::jst_date(astro::new_moon(k));
+    let end = astro::jst_date(astro::new_moon(k + 1));
+    let sector = |date: NaiveDate| (astro::sun_longitude(jst_midnight(date)) / 30.0).floor() as u32;
+    let (first, last) = (sector(start), sector(end));
+    if first == last {
+        return None;
+    }
+    // the 中気 at longitude 30 * (first + 1); 雨水 (330°) is in month 1
+    Some((first + 2) % 12 + 1)
+}
+
+// Julian day of 00:00 JST on a date.
+fn jst_midnight(date: NaiveDate) -> f64 {
+    astro::julian_day(date) - 9.0 / 24.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn rokuyo(year: i32, month: u32, day: u32) -> Rokuyo {
+        rokuyo_for(NaiveDate::from_ymd_opt(year, month, day).unwrap()).unwrap()
+    }
+
+    // 旧暦 dates from the new moons in the 国立天文台 暦要項; the 六曜 follows from (month + day) % 6.
+    #[test]
+    fn reference_dates() {
+        // 旧暦 2023-11-20, as printed on calendars; the request had 先勝, which is wrong
+        assert_eq!(rokuyo(2024, 1, 1), Rokuyo::Shakko);
+        // 旧正月 (旧暦 1/1) is always 先勝
+        assert_eq!(rokuyo(2023, 1, 22), Rokuyo::Sensho);
+        assert_eq!(rokuyo(2024, 2, 10), Rokuyo::Sensho);
+        assert_eq!(rokuyo(2025, 1, 29), Rokuyo::Sensho);
+        // 中秋 (旧暦 8/15)
+        assert_eq!(rokuyo(2024, 9, 17), Rokuyo::Butsumetsu);
+        // first day of 閏2月 2023 counts as month 2
+        assert_eq!(rokuyo(2023, 3, 22), Rokuyo::Tomobiki);
+    }
+
+    #[test]
+    fn years_outside_the_tables_fail() {
+        assert!(rokuyo_for(NaiveDate::from_ymd_opt(1899, 12, 31).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+//! # Sekki
+//! The 24 solar terms (二十四節気), e.g. 立春 on 2024-02-04.
+//!
+//! Dates are computed from the apparent longitude of the sun and are within a day of the
+//! NAOJ almanac. 春分 and 秋分 are taken from the equinox table when it covers the year,
+//! so they always fall on 春分の日 and 秋分の日.
+//!
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use serde::Serialize;
+use super::astro;
+use super::timebase::get_equinox_for_year;
+
+/// A solar term
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SolarTerm {
+    pub name: String, // name of the term (立春)
+    pub date: NaiveDate, // date in Japan
+}
+
+// Name and solar longitude of each term, in calendar order.
+const SOLAR_TERMS: [(&str, u32); 24] = [
+    ("小寒", 285), ("大寒", 300), ("立春", 315), ("雨水", 330), ("啓蟄", 345), ("春分", 0),
+    ("清明", 15), ("穀雨", 30), ("立夏", 45), ("小満", 60), ("芒種", 75), ("夏至", 90),
+    ("小暑", 105), ("大暑", 120), ("立秋", 135), ("処暑", 150), ("白露", 165), ("秋分", 180),
+    ("寒露", 195), ("霜降", 210), ("立冬", 225), ("小雪", 240), ("大雪", 255), ("冬至", 270),
+];
+
+/// Years for which the computed dates are reliable.
+pub const SUPPORTED_YEARS: std::ops::RangeInclusive<u32> = 1900..=2100;
+
+/// Get the 24 solar terms of a year, from 小寒 to 冬至.
+pub fn solar_terms(year: u32) -> Result<Vec<SolarTerm>> {
+    if !SUPPORTED_YEARS.contains(&year) {
+        return Err(anyhow!("solar terms are only available for {}-{}", SUPPORTED_YEARS.start(), SUPPORTED_YEARS.end()));
+    }
+    let equinoxes = get_equinox_for_year(year)?;
+    let terms = SOLAR_TERMS.iter().map(|&(name, longitude)| {
+        let date = match (name, equinoxes) {
+            ("春分", Some((vernal, _))) => vernal,
+            ("秋分", Some((_, autumnal))) => autumnal,
+            _ => sun_longitude_date(year, longitude),
+        };
+        SolarTerm { name: name.to_string(), date }
+    });
+    Ok(terms.collect())
+}
+
+/// Date in Japan on which the sun reaches `longitude` (in degrees) during `year`.
+///
+/// Longitudes from 285° are reached in January to March, before 春分.
+pub(crate) fn sun_longitude_date(year: u32, longitude: u32) -> NaiveDate {
+    let march_20 = NaiveDate::from_ymd_opt(year as i32, 3, 20).unwrap();
+    let offset = if longitude >= 285 { longitude as f64 - 360.0 } else { longitude as f64 };
+    let estimate = astro::julian_day(march_20) + offset / 360.0 * 365.2422;
+    astro::jst_date(astro::sun_reaches(longitude as f64, estimate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    // 春分, 夏至, 秋分 and 冬至 as published by NAOJ (国立天文台 暦要項).
+    const NAOJ: [(u32, [(u32, u32); 4]); 7] = [
+        (2024, [(3, 20), (6, 21), (9, 22), (12, 21)]),
+        (2025, [(3, 20), (6, 21), (9, 23), (12, 22)]),
+        (2026, [(3, 20), (6, 21), (9, 23), (12, 22)]),
+        (2027, [(3, 21), (6, 21), (9, 23), (12, 22)]),
+        (2028, [(3, 20), (6, 21), (9, 22), (12, 21)]),
+        (2029, [(3, 20), (6, 21), (9, 23), (12, 21)]),
+        (2030, [(3, 20), (6, 21), (9, 23), (12, 22)]),
+    ];
+
+    #[test]
+    fn equinoxes_and_solstices_match_naoj() {
+        for (year, dates) in NAOJ {
+            let terms = solar_terms(year).unwrap();
+            for ((name, longitude), (month, day)) in [("春分", 0), ("夏至", 90), ("秋分", 180), ("冬至", 270)].into_iter().zip(dates) {
+                let expected = NaiveDate::from_ymd_opt(year as i32, month, day).unwrap();
+                let term = terms.iter().find(|t| t.name == name).unwrap();
+                // 春分 and 秋分 come from the equinox table; the computed date is checked as well
+                for date in [term.date, sun_longitude_date(year, longitude)] {
+                    assert!((date - expected).num_days().abs() <= 1, "{} {}: {} instead of {}", year, name, date, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn terms_are_in_calendar_order() {
+        let terms = solar_terms(2024).unwrap();
+        assert_eq!(terms.len(), 24);
+        assert!(terms.windows(2).all(|pair| pair[0].date < pair[1].date));
+        assert!(solar_terms(2101).is_err());
+    }
+}
@@ -5,7 +5,7 @@
 //! ## Description
 //!
 //! Returns a list of dates that are set as holidays based on Japan's national holiday law for the year 2023 in vector format.
-//! Note that temporary holiday transfers, etc. are not supported.
+//! One-off holidays and moves declared by special laws (e.g. the 2019 enthronement and the 2020/2021 Olympics) are listed in `resources/special_holidays.csv`.
 //!
 //! See: [Japanese national holiday law](https://www8.cao.go.jp/chosei/shukujitsu/gaiyou.html)
 //!
@@ -14,25 +14,34 @@
 //! See: [Vernal Equinox Day](https://ja.wikipedia.org/wiki/%E6%98%A5%E5%88%86%E3%81%AE%E6%97%A5)
 //!
 //! ## Usage
-//!    use datebook;
-//!    use datebook::timebase::defaults;
 //! ```
-//!  fn main() {
-//!      let d = defaults().unwrap();
-//!     println!("{:?}", d);
-//!  }
+//!  use jpn_holidays_wasm::datebook::timebase::get_schedule;
+//!
+//!  let d = get_schedule().unwrap();
+//!  println!("{:?}", d);
 //! ```
 
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
 use csv;
+use chrono::NaiveDate;
+use std::fmt;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 #[allow(unused_imports)]
-use anyhow::{Result, Error};
+use anyhow::{anyhow, Context, Result, Error};
+use super::util::parse_holiday_date;
 const BASE_DATA: &[u8] = include_bytes!("../resources/base.csv");
 const BASE_EQUINOX: &[u8] = include_bytes!("../resources/equinox_base_dates.csv");
+const SPECIAL_HOLIDAYS: &[u8] = include_bytes!("../resources/special_holidays.csv");
+pub const VERNAL_EQUINOX_NAME: &str = "春分の日";
+pub const AUTUMNAL_EQUINOX_NAME: &str = "秋分の日";
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Condition {
     pub month: String,
-    pub n: u32,
+    /// n-th weekday of the month (1-5), `last` is -1
+    pub n: i32,
     pub weekday: String,
 }
 
@@ -42,89 +51,312 @@ pub struct Condition {
     pub date: Option<String>,
     pub relative: bool,
     pub condition: Option<Condition>,
+    pub effective_from: Option<u32>, // first year the holiday applies
+    pub effective_until: Option<u32>, // last year the holiday applies
+    pub id: String, // stable key shared by renamed holidays (体育の日 and スポーツの日 are "sports-day")
+    pub note: Option<String>, // `Holiday::note`, optional 8th column
 }
 
-#[derive(Debug)]
+impl BaseHolyday {
+    /// Whether the holiday applies in `year`.
+    pub fn is_effective(&self, year: u32) -> bool {
+        self.effective_from.is_none_or(|from| year >= from)
+            && self.effective_until.is_none_or(|until| year <= until)
+    }
+}
+
+/// Ordered by date, then by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EquinoxDay {
     pub name: String,
-    pub date: String,
+    pub date: String, // as written in the table (3/20)
+    pub parsed: NaiveDate, // `date` in the year of the row, parsed once when the table is read
 }
 
-#[derive(Debug)]
+/// Ordered by year.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Equinox {
     pub year: u32,
     pub equinox: Vec<EquinoxDay>,
 }
+
+impl Ord for EquinoxDay {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.parsed, &self.name, &self.date).cmp(&(other.parsed, &other.name, &other.date))
+    }
+}
+
+impl PartialOrd for EquinoxDay {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Equinox {
+    /// Vernal Equinox Day (春分の日) of the year.
+    pub fn vernal_equinox(&self) -> Option<&EquinoxDay> {
+        self.equinox.iter().find(|x| x.name == VERNAL_EQUINOX_NAME)
+    }
+
+    /// Autumnal Equinox Day (秋分の日) of the year.
+    pub fn autumnal_equinox(&self) -> Option<&EquinoxDay> {
+        self.equinox.iter().find(|x| x.name == AUTUMNAL_EQUINOX_NAME)
+    }
+}
+/// One-off holiday declared by a special law, such as the 2019 enthronement day.
+///
+/// A row with the name of a regular holiday moves that holiday to `date` for the year,
+/// as the Olympics laws did for 海の日, スポーツの日 and 山の日 in 2020 and 2021.
+#[derive(Debug, Deserialize)]
+pub struct SpecialHoliday {
+    pub year: u32,
+    pub date: String,
+    pub name: String,
+    #[serde(default)]
+    pub id: String, // `Holiday::id`; a row that moves a regular holiday keeps that holiday's id
+}
+
+// A row of equinox_base_dates.csv
+#[derive(Deserialize)]
+struct EquinoxRecord {
+    year: u32,
+    spring: String,
+    fall: String,
+}
+
+/// A row skipped while reading holiday data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParseWarning {
+    pub line: Option<u64>, // line in the CSV (the header is line 1), when known
+    pub message: String,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl ParseWarning {
+    pub fn new(line: Option<u64>, error: Error) -> Self {
+        ParseWarning { line, message: format!("{:#}", error) }
+    }
+}
+
+// Fail with every warning, for callers that need every row.
+pub(crate) fn strict<T>((values, warnings): (Vec<T>, Vec<ParseWarning>)) -> Result<Vec<T>> {
+    if warnings.is_empty() {
+        return Ok(values);
+    }
+    let messages: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+    Err(anyhow!("{}", messages.join("; ")))
+}
+
 // List of Japanese Holidays throughout the Year
 #[allow(dead_code)]
 pub fn get_schedule()-> Result<Vec<BaseHolyday>> {
-    //let path = format!("{}/src/utils/base.csv", env!("CARGO_MANIFEST_DIR"));
+    parse_schedule(BASE_DATA)
+}
+
+/// Holiday rules that apply in `year` (see `BaseHolyday::is_effective`).
+pub fn get_schedule_for_year(year: u32) -> Result<Vec<BaseHolyday>> {
+    strict(get_schedule_for_year_with_warnings(year))
+}
+
+/// Like `get_schedule_for_year`, skipping malformed rows instead of failing.
+pub fn get_schedule_for_year_with_warnings(year: u32) -> (Vec<BaseHolyday>, Vec<ParseWarning>) {
+    let (rows, warnings) = parse_schedule_with_warnings(BASE_DATA);
+    (rows.into_iter().filter(|d| d.is_effective(year)).collect(), warnings)
+}
+
+/// Parse holiday rules in the layout of `resources/base.csv`.
+pub fn parse_schedule(data: &[u8])-> Result<Vec<BaseHolyday>> {
+    strict(parse_schedule_with_warnings(data))
+}
+
+/// Like `parse_schedule`, returning the valid rows and a warning for each malformed one.
+pub fn parse_schedule_with_warnings(data: &[u8]) -> (Vec<BaseHolyday>, Vec<ParseWarning>) {
     let mut base_dates: Vec<BaseHolyday> = Vec::new();
-    let mut reader = csv::Reader::from_reader(BASE_DATA);
+    let mut warnings = Vec::new();
+    let mut reader = csv::Reader::from_reader(data);
     for result in reader.records() {
-        match result {
-            Ok(record) => {
-                let m: Vec<String> = record.iter().map(|x| x.to_string()).collect();
-                let value = BaseHolyday {
-                    name: m[0].to_string(),
-                    date: if m[1].is_empty() { None } else { Some(m[1].to_string())},
-                    relative: match m[2].parse() {
-                        Ok(v) => v,
-                        Err(_) => false,
-                    },
-                    condition: if m[3].is_empty() { None } else {
-                        let c: Vec<String> = m[3].split(":").map(|x| x.to_string()).collect();
-                        Some(Condition {
-                            month: c[0].to_string(),
-                            n: match c[1].parse() {
-                                Ok(v) => v,
-                                Err(_) => 0,
-                            },
-                            weekday: c[2].to_string(),
-                        })
-                    },
-                };
-                base_dates.push(value);
-            },
-            Err(err) => return Err(err.into()),
+        let (line, row) = match result {
+            Ok(record) => (record.position().map(|p| p.line()), parse_schedule_row(&record)),
+            Err(err) => (err.position().map(|p| p.line()), Err(err.into())),
+        };
+        match row {
+            Ok(value) => base_dates.push(value),
+            Err(e) => warnings.push(ParseWarning::new(line, e)),
         }
     }
+    (base_dates, warnings)
+}
 
-    Ok(base_dates)
+fn parse_schedule_row(record: &csv::StringRecord) -> Result<BaseHolyday> {
+    let m: Vec<String> = record.iter().map(|x| x.to_string()).collect();
+    if m.len() < 6 {
+        return Err(anyhow!("expected 6 columns but found {}", m.len()));
+    }
+    Ok(BaseHolyday {
+        name: m[0].to_string(),
+        date: if m[1].is_empty() { None } else { Some(m[1].to_string())},
+        relative: m[2].parse().unwrap_or_default(),
+        condition: if m[3].is_empty() { None } else {
+            let c: Vec<String> = m[3].split(':').map(|x| x.to_string()).collect();
+            if c.len() != 3 {
+                return Err(anyhow!("row '{}': invalid condition '{}'", m[0], m[3]));
+            }
+            Some(Condition {
+                month: c[0].to_string(),
+                n: match c[1].trim() {
+                    "last" => -1,
+                    v => v.parse()
+                        .with_context(|| format!("row '{}': invalid n '{}'", m[0], v))?,
+                },
+                weekday: c[2].to_string(),
+            })
+        },
+        effective_from: parse_year(&m[0], &m[4])?,
+        effective_until: parse_year(&m[0], &m[5])?,
+        id: m.get(6).cloned().unwrap_or_default(),
+        note: m.get(7).filter(|x| !x.is_empty()).cloned(),
+    })
+}
+
+fn parse_year(name: &str, value: &str) -> Result<Option<u32>> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let year = value.parse()
+        .with_context(|| format!("row '{}': invalid year '{}'", name, value))?;
+    Ok(Some(year))
 }
 
 //　Basic data on Japanese national holidays, the vernal equinox and autumnal equinox, will be returned.
 #[allow(dead_code)]
 pub fn get_equinox_dates()->Result<Vec<Equinox>> {
-    let mut equinox_dates: Vec<Vec<String>> = Vec::new();
-    let mut reader = csv::Reader::from_reader(BASE_EQUINOX);
-    let mut records: Vec<Equinox> = Vec::new();
-    for result in reader.records() {
+    parse_equinox_dates(BASE_EQUINOX)
+}
+
+/// One-off holidays that are not derived from the standing rules.
+pub fn get_special_holidays() -> Result<Vec<SpecialHoliday>> {
+    parse_special_holidays(SPECIAL_HOLIDAYS)
+}
+
+/// Like `get_special_holidays`, skipping malformed rows instead of failing.
+pub fn get_special_holidays_with_warnings() -> (Vec<SpecialHoliday>, Vec<ParseWarning>) {
+    parse_special_holidays_with_warnings(SPECIAL_HOLIDAYS)
+}
+
+/// Parse special holidays in the layout of `resources/special_holidays.csv`.
+pub fn parse_special_holidays(data: &[u8]) -> Result<Vec<SpecialHoliday>> {
+    strict(parse_special_holidays_with_warnings(data))
+}
+
+/// Like `parse_special_holidays`, returning the valid rows and a warning for each malformed one.
+pub fn parse_special_holidays_with_warnings(data: &[u8]) -> (Vec<SpecialHoliday>, Vec<ParseWarning>) {
+    deserialize_rows(data)
+}
+
+// Deserialize every row, collecting the ones that fail as warnings.
+fn deserialize_rows<T: DeserializeOwned>(data: &[u8]) -> (Vec<T>, Vec<ParseWarning>) {
+    let mut rows = Vec::new();
+    let mut warnings = Vec::new();
+    let mut reader = csv::Reader::from_reader(data);
+    for result in reader.deserialize() {
         match result {
-            Ok(record) => {
-                let m: Vec<String> = record.iter().map(|x| x.to_string()).collect();
-                equinox_dates.push(m);
-            },
-            Err(err) => println!("{:?}", err),
+            Ok(row) => rows.push(row),
+            Err(err) => warnings.push(ParseWarning::new(err.position().map(|p| p.line()), err.into())),
         }
     }
-    for date in equinox_dates {
-        let year = date[0].parse::<u32>().unwrap();
-        let day = Equinox {
-            year: year,
-            equinox: vec![
-                EquinoxDay {
-                    name: "春分の日".to_string(),
-                    date: date[1].to_string(),
-                },
-                EquinoxDay {
-                    name: "秋分の日".to_string(),
-                    date: date[2].to_string(),
-                },
-            ],
-        };
-        records.push(day);
+    (rows, warnings)
+}
+
+/// Get the sorted list of years covered by the equinox table.
+///
+/// Holidays can only be computed for these years. The list is read once and cached.
+pub fn all_years_with_equinox_data() -> Vec<u32> {
+    equinox_table().0.keys().copied().collect()
+}
+
+/// The embedded equinox table by year, with the rows that were skipped.
+///
+/// The table is parsed once and cached.
+pub fn equinox_table() -> &'static (BTreeMap<u32, Equinox>, Vec<ParseWarning>) {
+    static TABLE: OnceLock<(BTreeMap<u32, Equinox>, Vec<ParseWarning>)> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let (equinoxes, warnings) = get_equinox_dates_with_warnings();
+        (equinoxes.into_iter().map(|e| (e.year, e)).collect(), warnings)
+    })
+}
+
+/// Like `get_equinox_dates`, skipping malformed rows instead of failing.
+pub fn get_equinox_dates_with_warnings() -> (Vec<Equinox>, Vec<ParseWarning>) {
+    parse_equinox_dates_with_warnings(BASE_EQUINOX)
+}
+
+/// Parse equinox dates in the layout of `resources/equinox_base_dates.csv`.
+pub fn parse_equinox_dates(data: &[u8])->Result<Vec<Equinox>> {
+    strict(parse_equinox_dates_with_warnings(data))
+}
+
+/// Like `parse_equinox_dates`, returning the valid rows and a warning for each malformed one.
+pub fn parse_equinox_dates_with_warnings(data: &[u8]) -> (Vec<Equinox>, Vec<ParseWarning>) {
+    let (rows, mut warnings) = deserialize_rows::<EquinoxRecord>(data);
+    let mut records = Vec::new();
+    for row in rows {
+        let days = [(VERNAL_EQUINOX_NAME, row.spring), (AUTUMNAL_EQUINOX_NAME, row.fall)]
+            .into_iter()
+            .map(|(name, date)| {
+                let parsed = parse_holiday_date(&date, Some(row.year))
+                    .with_context(|| format!("year {}: invalid {} date '{}'", row.year, name, date))?;
+                Ok(EquinoxDay { name: name.to_string(), date, parsed })
+            })
+            .collect::<Result<Vec<_>>>();
+        match days {
+            Ok(equinox) => records.push(Equinox { year: row.year, equinox }),
+            Err(e) => warnings.push(ParseWarning::new(None, e)),
+        }
+    }
+    (records, warnings)
+}
+
+
+/// Get the vernal and autumnal equinox dates of a year as `(vernal, autumnal)`.
+///
+/// Returns `None` when the year is outside the equinox table.
+/// The dates are not strictly calculated, as they are affected by the actual astronomical motion of the celestial bodies;
+/// they are the projected dates from 2020 to 2050.
+pub fn get_equinox_for_year(year: u32) -> Result<Option<(NaiveDate, NaiveDate)>> {
+    let (equinoxes, warnings) = equinox_table();
+    if let Some(w) = warnings.first() {
+        return Err(anyhow!("{}", w));
     }
-    Ok(records)
+    let target = match equinoxes.get(&year) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    let parsed = |day: Option<&EquinoxDay>| -> Result<NaiveDate> {
+        Ok(day.ok_or_else(|| anyhow!("missing equinox in {}", year))?.parsed)
+    };
+
+    Ok(Some((parsed(target.vernal_equinox())?, parsed(target.autumnal_equinox())?)))
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn has_rule(year: u32, name: &str) -> bool {
+        get_schedule_for_year(year).unwrap().iter().any(|h| h.name == name)
+    }
+
+    #[test]
+    fn mountain_day_starts_in_2016() {
+        assert!(!has_rule(2015, "山の日"));
+        assert!(has_rule(2016, "山の日"));
+    }
+}
@@ -0,0 +1,143 @@
+//! # Translation
+//! Holiday names in other languages, keyed by `Holiday::id` and locale.
+//!
+//! "ja" is the name in the data and "en" ships with the crate; more locales can be
+//! registered at runtime with `register`. A substitute holiday is translated as
+//! `<substitute> (<original>)`, e.g. `Substitute Holiday (Culture Day)`.
+//!
+//! Custom holidays have the id `custom:<name>`, so `register("en", "custom:創立記念日", "Founding Day")`
+//! translates one and its substitute holidays.
+//!
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use super::calendar::Holiday;
+
+const BUILTIN_TRANSLATIONS: &[u8] = include_bytes!("../resources/translations.csv");
+
+// Id looked up for the `振替休日` part of substitute holidays
+const SUBSTITUTE_ID: &str = "substitute";
+
+// A row of translations.csv
+#[derive(Deserialize)]
+struct TranslationRecord {
+    locale: String,
+    id: String,
+    name: String,
+}
+
+/// Holiday names by locale and id.
+#[derive(Debug, Clone, Default)]
+pub struct Translations {
+    names: HashMap<String, HashMap<String, String>>, // locale -> id -> name
+}
+
+/// A translated holiday name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Translation {
+    pub name: String,
+    pub translated: bool, // `false` when falling back to the Japanese name
+}
+
+impl Translations {
+    /// The translations that ship with the crate ("en").
+    pub fn builtin() -> Result<Self> {
+        let mut translations = Translations::default();
+        let mut reader = csv::Reader::from_reader(BUILTIN_TRANSLATIONS);
+        for result in reader.deserialize() {
+            let row: TranslationRecord = result?;
+            translations.insert(&row.locale, &row.id, &row.name);
+        }
+        Ok(translations)
+    }
+
+    /// Add or replace the name of the holiday `id` in `locale`.
+    pub fn insert(&mut self, locale: &str, id: &str, name: &str) {
+        self.names.entry(locale.to_lowercase()).or_default().insert(id.to_string(), name.to_string());
+    }
+
+    /// Name of the holiday `id` in `locale`, trying the language alone for a tagged locale ("en-US").
+    pub fn get(&self, locale: &str, id: &str) -> Option<&str> {
+        let locale = locale.to_lowercase();
+        let language = locale.split(['-', '_']).next().unwrap_or_default();
+        let name = [locale.as_str(), language].into_iter()
+            .find_map(|l| self.names.get(l)?.get(id))?;
+        Some(name)
+    }
+
+    /// Name of `holiday` in `locale`, falling back to the Japanese name.
+    pub fn translate(&self, holiday: &Holiday, locale: &str) -> Translation {
+        let name = if is_japanese(locale) {
+            Some(holiday.name.clone())
+        } else if let Some(original) = holiday.id.strip_prefix("substitute:") {
+            self.get(locale, SUBSTITUTE_ID)
+                .zip(self.get(locale, original))
+                .map(|(substitute, original)| format!("{} ({})", substitute, original))
+        } else {
+            self.get(locale, &holiday.id).map(str::to_string)
+        };
+        match name {
+            Some(name) => Translation { name, translated: true },
+            None => Translation { name: holiday.name.clone(), translated: false },
+        }
+    }
+}
+
+fn is_japanese(locale: &str) -> bool {
+    let locale = locale.to_lowercase();
+    locale == "ja" || locale.starts_with("ja-") || locale.starts_with("ja_")
+}
+
+// Process-wide translations used by the renderers and the wasm exports.
+fn registry() -> &'static RwLock<Translations> {
+    static REGISTRY: OnceLock<RwLock<Translations>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(Translations::builtin().unwrap_or_default()))
+}
+
+/// Register the name of the holiday `id` in `locale` for every later lookup.
+pub fn register(locale: &str, id: &str, name: &str) {
+    registry().write().unwrap_or_else(|e| e.into_inner()).insert(locale, id, name);
+}
+
+/// Name of `holiday` in `locale` from the registered translations, falling back to Japanese.
+pub fn translate(holiday: &Holiday, locale: &str) -> Translation {
+    registry().read().unwrap_or_else(|e| e.into_inner()).translate(holiday, locale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+    use crate::datebook::calendar::{holiday_on, CustomHolidays, HolidayCalendar};
+
+    #[test]
+    fn substitute_translation_names_the_same_holiday_as_the_japanese_name() {
+        let substitute = holiday_on(NaiveDate::from_ymd_opt(2020, 5, 6).unwrap()).unwrap().unwrap();
+        let translations = Translations::builtin().unwrap();
+        assert_eq!(substitute.name, "振替休日(憲法記念日)");
+        assert_eq!(translations.translate(&substitute, "en").name, "Substitute Holiday (Constitution Memorial Day)");
+    }
+
+    #[test]
+    fn custom_holidays_are_translated_by_their_id() {
+        let custom = CustomHolidays::new().annual("創立記念日", 9, 1).substitutes(true);
+        let calendar = HolidayCalendar::with_custom(2024, &custom).unwrap();
+        let mut translations = Translations::builtin().unwrap();
+        translations.insert("en", "custom:創立記念日", "Founding Day");
+        let names: Vec<String> = calendar.holidays().iter()
+            .filter(|h| h.name.contains("創立記念日"))
+            .map(|h| translations.translate(h, "en").name)
+            .collect();
+        assert_eq!(names, ["Founding Day", "Substitute Holiday (Founding Day)"]);
+    }
+
+    #[test]
+    fn missing_translation_keeps_the_japanese_name() {
+        let new_year = holiday_on(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()).unwrap().unwrap();
+        let translations = Translations::builtin().unwrap();
+        assert_eq!(translations.translate(&new_year, "ko"), Translation { name: "元旦".to_string(), translated: false });
+        assert_eq!(translations.translate(&new_year, "en-US"), Translation { name: "New Year's Day".to_string(), translated: true });
+    }
+}
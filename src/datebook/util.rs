@@ -0,0 +1,163 @@
+//! # Util
+//! Small helpers shared by the datebook modules.
+//!
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, Duration, Months, NaiveDate, Weekday};
+
+pub mod jp_format;
+pub use jp_format::weekday_kanji;
+
+/// Parse a weekday name.
+///
+/// Accepts English full names and 3-letter abbreviations case-insensitively,
+/// and Japanese names such as `月曜日`, `月曜` or `月`.
+pub fn get_weekday_from_string(char: &str)-> Option<Weekday> {
+    match char.trim().to_lowercase().as_str() {
+        "monday" | "mon" | "月曜日" | "月曜" | "月" => Some(Weekday::Mon),
+        "tuesday" | "tue" | "火曜日" | "火曜" | "火" => Some(Weekday::Tue),
+        "wednesday" | "wed" | "水曜日" | "水曜" | "水" => Some(Weekday::Wed),
+        "thursday" | "thu" | "木曜日" | "木曜" | "木" => Some(Weekday::Thu),
+        "friday" | "fri" | "金曜日" | "金曜" | "金" => Some(Weekday::Fri),
+        "saturday" | "sat" | "土曜日" | "土曜" | "土" => Some(Weekday::Sat),
+        "sunday" | "sun" | "日曜日" | "日曜" | "日" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// Parse a month name into its number (1-12).
+///
+/// Accepts English full names and 3-letter abbreviations case-insensitively,
+/// and Japanese names such as `一月` or `1月`.
+pub fn get_month_num_from_string(char: &str) -> Option<u32> {
+    match char.trim().to_lowercase().as_str() {
+        "january" | "jan" | "一月" | "1月" => Some(1),
+        "february" | "feb" | "二月" | "2月" => Some(2),
+        "march" | "mar" | "三月" | "3月" => Some(3),
+        "april" | "apr" | "四月" | "4月" => Some(4),
+        "may" | "五月" | "5月" => Some(5),
+        "june" | "jun" | "六月" | "6月" => Some(6),
+        "july" | "jul" | "七月" | "7月" => Some(7),
+        "august" | "aug" | "八月" | "8月" => Some(8),
+        "september" | "sep" | "九月" | "9月" => Some(9),
+        "october" | "oct" | "十月" | "10月" => Some(10),
+        "november" | "nov" | "十一月" | "11月" => Some(11),
+        "december" | "dec" | "十二月" | "12月" => Some(12),
+        _ => None,
+    }
+}
+
+/// Parse a date written as `YYYY-MM-DD` or `YYYY/MM/DD`, or as `M/D` such as `1/1`
+/// or `12/23` in `year`, as in the embedded CSV files.
+pub(crate) fn parse_holiday_date(s: &str, year: Option<u32>) -> Result<NaiveDate> {
+    let s = s.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d").or_else(|_| NaiveDate::parse_from_str(s, "%Y/%m/%d")) {
+        return Ok(date);
+    }
+    let month_day = year.zip(s.split_once('/'))
+        .and_then(|(year, (m, d))| Some((year, m.trim().parse::<u32>().ok()?, d.trim().parse::<u32>().ok()?)));
+    match month_day {
+        Some((year, month, day)) => NaiveDate::from_ymd_opt(year as i32, month, day)
+            .ok_or_else(|| anyhow!("{}/{}/{} is not a valid date", year, month, day)),
+        None => Err(anyhow!("invalid date '{}', expected YYYY-MM-DD, YYYY/MM/DD or M/D", s)),
+    }
+}
+
+/// Occurrence of a weekday within a month.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NthWeek {
+    First,
+    Second,
+    Third,
+    Fourth,
+    Fifth,
+    Last,
+}
+
+impl TryFrom<i32> for NthWeek {
+    type Error = anyhow::Error;
+
+    /// `1..=5`, or `-1` for the last occurrence.
+    fn try_from(n: i32) -> Result<Self> {
+        match n {
+            1 => Ok(NthWeek::First),
+            2 => Ok(NthWeek::Second),
+            3 => Ok(NthWeek::Third),
+            4 => Ok(NthWeek::Fourth),
+            5 => Ok(NthWeek::Fifth),
+            -1 => Ok(NthWeek::Last),
+            _ => Err(anyhow!("invalid week {} (expected 1-5 or -1 for the last)", n)),
+        }
+    }
+}
+
+/// Get the n-th `weekday` of a month, e.g. the second Monday of October.
+///
+/// Returns `None` when the month has no such occurrence (a fifth Monday) or the month is invalid.
+pub fn nth_weekday_of_month(year: i32, month: u32, weekday: Weekday, n: NthWeek) -> Option<NaiveDate> {
+    let nth = match n {
+        NthWeek::First => 1,
+        NthWeek::Second => 2,
+        NthWeek::Third => 3,
+        NthWeek::Fourth => 4,
+        NthWeek::Fifth => 5,
+        NthWeek::Last => {
+            let last = NaiveDate::from_ymd_opt(year, month, 1)?
+                .checked_add_months(Months::new(1))?
+                - Duration::days(1);
+            let offset = (7 + last.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+            return Some(last - Duration::days(offset as i64));
+        }
+    };
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, nth)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const WEEKDAYS: [Weekday; 7] = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun];
+
+    #[test]
+    fn holiday_dates_with_and_without_a_year() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(parse_holiday_date("2024-12-23", None).unwrap(), date(2024, 12, 23));
+        assert_eq!(parse_holiday_date(" 2024/1/1 ", Some(2030)).unwrap(), date(2024, 1, 1));
+        assert_eq!(parse_holiday_date("12/23", Some(2030)).unwrap(), date(2030, 12, 23));
+        assert_eq!(parse_holiday_date("2/29", Some(2024)).unwrap(), date(2024, 2, 29));
+        assert_eq!(parse_holiday_date("2/29", Some(2023)).unwrap_err().to_string(), "2023/2/29 is not a valid date");
+        assert!(parse_holiday_date("12/23", None).is_err());
+        assert!(parse_holiday_date("2024-13-01", Some(2024)).is_err());
+    }
+
+    // Every weekday of every month in 2023 and 2024 (a leap year), against the dates found by walking the month.
+    #[test]
+    fn nth_weekday_of_month_matches_a_walk_of_the_month() {
+        let (mut four, mut five) = (0, 0);
+        for year in [2023, 2024] {
+            for month in 1..=12 {
+                let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+                let days: Vec<NaiveDate> = first.iter_days().take_while(|d| d.month() == month).collect();
+                for weekday in WEEKDAYS {
+                    let expected: Vec<NaiveDate> = days.iter().copied().filter(|d| d.weekday() == weekday).collect();
+                    let nth = [NthWeek::First, NthWeek::Second, NthWeek::Third, NthWeek::Fourth, NthWeek::Fifth]
+                        .map(|n| nth_weekday_of_month(year, month, weekday, n));
+                    assert_eq!(nth.iter().flatten().copied().collect::<Vec<_>>(), expected, "{} {}-{}", weekday, year, month);
+                    assert_eq!(nth_weekday_of_month(year, month, weekday, NthWeek::Last), expected.last().copied());
+                    match expected.len() {
+                        4 => four += 1,
+                        5 => five += 1,
+                        n => panic!("{} {}s in {}-{}", n, weekday, year, month),
+                    }
+                }
+            }
+        }
+        assert!(four > 0 && five > 0);
+    }
+
+    #[test]
+    fn nth_weekday_of_an_invalid_month_is_none() {
+        assert_eq!(nth_weekday_of_month(2024, 13, Weekday::Mon, NthWeek::First), None);
+        assert_eq!(nth_weekday_of_month(2024, 13, Weekday::Mon, NthWeek::Last), None);
+    }
+}
@@ -0,0 +1,50 @@
+//! # Japanese formatting
+//! Japanese names of weekdays and months.
+//!
+use chrono::Weekday;
+
+/// Get the kanji for a weekday (月火水木金土日).
+pub fn weekday_kanji(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "月",
+        Weekday::Tue => "火",
+        Weekday::Wed => "水",
+        Weekday::Thu => "木",
+        Weekday::Fri => "金",
+        Weekday::Sat => "土",
+        Weekday::Sun => "日",
+    }
+}
+
+const WASO_MONTH_NAMES: [&str; 12] = [
+    "睦月", "如月", "弥生", "卯月", "皐月", "水無月", "文月", "葉月", "長月", "神無月", "霜月", "師走",
+];
+
+/// Get the traditional name of a month (和風月名), e.g. `睦月` for 1.
+///
+/// Returns `None` unless `month` is 1-12.
+pub fn waso_month_name(month: u32) -> Option<&'static str> {
+    let index = month.checked_sub(1)? as usize;
+    WASO_MONTH_NAMES.get(index).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn month_names() {
+        assert_eq!(waso_month_name(1), Some("睦月"));
+        assert_eq!(waso_month_name(6), Some("水無月"));
+        assert_eq!(waso_month_name(12), Some("師走"));
+        assert_eq!(waso_month_name(0), None);
+        assert_eq!(waso_month_name(13), None);
+    }
+
+    #[test]
+    fn weekday_names() {
+        assert_eq!(weekday_kanji(Weekday::Mon), "月");
+        assert_eq!(weekday_kanji(Weekday::Sun), "日");
+    }
+}
@@ -0,0 +1,347 @@
+//! # Workday
+//! Working-day counts for payroll and scheduling: weekdays that are not holidays.
+//!
+//! Substitute holidays and 国民の休日 are non-working days, like any other holiday.
+//!
+//! The free functions assume a Saturday and Sunday weekend; `BusinessCalendar` takes
+//! another one, such as Friday and Saturday.
+//!
+use std::str::FromStr;
+use anyhow::{anyhow, Error, Result};
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+use super::calendar::HolidaySet;
+
+/// Days of the week that are not business days; the default is Saturday and Sunday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WeekendDays(u8); // bit n is the weekday n days after Monday
+
+impl WeekendDays {
+    pub fn new(days: &[Weekday]) -> Self {
+        WeekendDays(days.iter().fold(0, |bits, d| bits | 1 << d.num_days_from_monday()))
+    }
+
+    /// Check whether `weekday` is a weekend day.
+    pub fn contains(&self, weekday: Weekday) -> bool {
+        self.0 & (1 << weekday.num_days_from_monday()) != 0
+    }
+}
+
+impl Default for WeekendDays {
+    fn default() -> Self {
+        WeekendDays::new(&[Weekday::Sat, Weekday::Sun])
+    }
+}
+
+/// Business days under a given weekend; a business day is neither a weekend day nor a holiday.
+///
+/// `BusinessCalendar::default()` has a Saturday and Sunday weekend, as the free functions do.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BusinessCalendar {
+    pub weekend: WeekendDays,
+}
+
+impl BusinessCalendar {
+    pub fn new(weekend: WeekendDays) -> Self {
+        BusinessCalendar { weekend }
+    }
+
+    /// Check whether `date` is a business day.
+    ///
+    /// Fails outside the years of the equinox table.
+    pub fn is_business_day(&self, date: NaiveDate) -> Result<bool> {
+        if self.weekend.contains(date.weekday()) {
+            return Ok(false);
+        }
+        Ok(!super::calendar::is_holiday(date)?)
+    }
+
+    /// Get the first business day after `date`.
+    pub fn next_business_day(&self, date: NaiveDate) -> Result<NaiveDate> {
+        let mut day = date;
+        for _ in 0..30 {
+            day += Duration::days(1);
+            if self.is_business_day(day)? {
+                return Ok(day);
+            }
+        }
+        Err(anyhow!("no business day found within 30 days after {}", date))
+    }
+
+    /// Business days of a month, in order.
+    pub fn business_days(&self, year: u32, month: u32) -> Result<Vec<NaiveDate>> {
+        let first = NaiveDate::from_ymd_opt(year as i32, month, 1)
+            .ok_or_else(|| anyhow!("invalid month {}-{}", year, month))?;
+        let last = first.checked_add_months(Months::new(1))
+            .ok_or_else(|| anyhow!("year {} is out of range", year))?;
+        self.business_days_between(year, first, last)
+    }
+
+    /// Count the working days of a month.
+    pub fn working_days_in_month(&self, year: u32, month: u32) -> Result<u32> {
+        Ok(self.business_days(year, month)?.len() as u32)
+    }
+
+    /// Count the working days of a year.
+    pub fn working_days_in_year(&self, year: u32) -> Result<u32> {
+        let first = NaiveDate::from_ymd_opt(year as i32, 1, 1)
+            .ok_or_else(|| anyhow!("year {} is out of range", year))?;
+        let last = first.checked_add_months(Months::new(12))
+            .ok_or_else(|| anyhow!("year {} is out of range", year))?;
+        Ok(self.business_days_between(year, first, last)?.len() as u32)
+    }
+
+    /// Get the first business day of a month.
+    pub fn first_business_day(&self, year: u32, month: u32) -> Result<NaiveDate> {
+        self.nth_business_day(year, month, 1)
+    }
+
+    /// Get the last business day of a month.
+    pub fn last_business_day(&self, year: u32, month: u32) -> Result<NaiveDate> {
+        self.business_days(year, month)?
+            .last()
+            .copied()
+            .ok_or_else(|| anyhow!("no business day in {}-{}", year, month))
+    }
+
+    /// Get the `n`-th business day of a month, counting from 1.
+    pub fn nth_business_day(&self, year: u32, month: u32, n: u32) -> Result<NaiveDate> {
+        let days = self.business_days(year, month)?;
+        n.checked_sub(1)
+            .and_then(|i| days.get(i as usize))
+            .copied()
+            .ok_or_else(|| anyhow!("{}-{} has {} business days, not {}", year, month, days.len(), n))
+    }
+
+    /// Move `date` to a business day by `rule`; a business day is returned as is.
+    pub fn adjust(&self, date: NaiveDate, rule: RollRule) -> Result<NaiveDate> {
+        let roll = |step: i64| -> Result<NaiveDate> {
+            let mut day = date;
+            for _ in 0..30 {
+                if self.is_business_day(day)? {
+                    return Ok(day);
+                }
+                day += Duration::days(step);
+            }
+            Err(anyhow!("no business day found within 30 days of {}", date))
+        };
+        match rule {
+            RollRule::Preceding => roll(-1),
+            RollRule::Following => roll(1),
+            RollRule::ModifiedFollowing => {
+                let day = roll(1)?;
+                if day.month() == date.month() { Ok(day) } else { roll(-1) }
+            }
+            RollRule::ModifiedPreceding => {
+                let day = roll(-1)?;
+                if day.month() == date.month() { Ok(day) } else { roll(1) }
+            }
+        }
+    }
+
+    /// Paydays of a year: `day_of_month` of each month moved by `rule`.
+    pub fn paydays(&self, year: u32, day_of_month: u32, rule: RollRule) -> Result<Vec<NaiveDate>> {
+        if !(1..=31).contains(&day_of_month) {
+            return Err(anyhow!("invalid day of month {}", day_of_month));
+        }
+        (1..=12).map(|month| {
+            let first = NaiveDate::from_ymd_opt(year as i32, month, 1)
+                .ok_or_else(|| anyhow!("year {} is out of range", year))?;
+            let last = first.checked_add_months(Months::new(1))
+                .ok_or_else(|| anyhow!("year {} is out of range", year))? - Duration::days(1);
+            let date = first + Duration::days(day_of_month.min(last.day()) as i64 - 1);
+            self.adjust(date, rule)
+        }).collect()
+    }
+
+    /// Estimate a delivery date; see the free function `estimate`.
+    pub fn estimate(&self, from: NaiveDateTime, business_days: u32, cutoff_hour: u32) -> Result<NaiveDate> {
+        if cutoff_hour > 24 {
+            return Err(anyhow!("invalid cutoff hour {}", cutoff_hour));
+        }
+        let date = from.date();
+        let mut day = if from.hour() < cutoff_hour && self.is_business_day(date)? {
+            date
+        } else {
+            self.next_business_day(date)?
+        };
+        for _ in 0..business_days {
+            day = self.next_business_day(day)?;
+        }
+        Ok(day)
+    }
+
+    // Business days in `from..until`, which must fall within `year`.
+    fn business_days_between(&self, year: u32, from: NaiveDate, until: NaiveDate) -> Result<Vec<NaiveDate>> {
+        let holidays = HolidaySet::new(year)?;
+        Ok(from.iter_days()
+            .take_while(|d| *d < until)
+            .filter(|d| !self.weekend.contains(d.weekday()) && !holidays.contains(*d))
+            .collect())
+    }
+}
+
+/// Count the working days of a month (weekdays that are not holidays).
+pub fn working_days_in_month(year: u32, month: u32) -> Result<u32> {
+    BusinessCalendar::default().working_days_in_month(year, month)
+}
+
+/// Count the working days of a year (weekdays that are not holidays).
+pub fn working_days_in_year(year: u32) -> Result<u32> {
+    BusinessCalendar::default().working_days_in_year(year)
+}
+
+/// Get the first business day of a month.
+pub fn first_business_day(year: u32, month: u32) -> Result<NaiveDate> {
+    BusinessCalendar::default().first_business_day(year, month)
+}
+
+/// Get the last business day of a month.
+pub fn last_business_day(year: u32, month: u32) -> Result<NaiveDate> {
+    BusinessCalendar::default().last_business_day(year, month)
+}
+
+/// Get the `n`-th business day of a month, counting from 1.
+///
+/// For custom closures such as 年末年始, use `HolidayCalendar::business_days` instead.
+pub fn nth_business_day(year: u32, month: u32, n: u32) -> Result<NaiveDate> {
+    BusinessCalendar::default().nth_business_day(year, month, n)
+}
+
+/// How a date that is not a business day is moved, as for paydays and settlement dates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RollRule {
+    Preceding, // the previous business day
+    Following, // the next business day
+    ModifiedFollowing, // the next business day, or the previous one if that is in the next month
+    ModifiedPreceding, // the previous business day, or the next one if that is in the previous month
+}
+
+impl FromStr for RollRule {
+    type Err = Error;
+
+    /// Parse a rule by its serialized name ("modified_following").
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "preceding" => Ok(RollRule::Preceding),
+            "following" => Ok(RollRule::Following),
+            "modified_following" => Ok(RollRule::ModifiedFollowing),
+            "modified_preceding" => Ok(RollRule::ModifiedPreceding),
+            _ => Err(anyhow!("unknown roll rule '{}'", s)),
+        }
+    }
+}
+
+/// Move `date` to a business day by `rule`; a business day is returned as is.
+pub fn adjust(date: NaiveDate, rule: RollRule) -> Result<NaiveDate> {
+    BusinessCalendar::default().adjust(date, rule)
+}
+
+/// Paydays of a year: `day_of_month` of each month moved by `rule`.
+///
+/// Months shorter than `day_of_month` use their last day, so 31 means the end of the month.
+pub fn paydays(year: u32, day_of_month: u32, rule: RollRule) -> Result<Vec<NaiveDate>> {
+    BusinessCalendar::default().paydays(year, day_of_month, rule)
+}
+
+/// Estimate a delivery date: `business_days` business days after the day an order placed
+/// at `from` is processed.
+///
+/// An order is processed the same day when that is a business day and `from` is before
+/// `cutoff_hour` (0-24), otherwise on the next business day; a cutoff of 0 always waits.
+pub fn estimate(from: NaiveDateTime, business_days: u32, cutoff_hour: u32) -> Result<NaiveDate> {
+    BusinessCalendar::default().estimate(from, business_days, cutoff_hour)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use crate::datebook::calendar::{CustomHolidays, HolidayCalendar};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn working_days_skip_weekends_and_holidays() {
+        // May 2024: 23 weekdays, less 5/3 憲法記念日 and 5/6 振替休日
+        assert_eq!(working_days_in_month(2024, 5).unwrap(), 21);
+        // June 2024 has no holidays
+        assert_eq!(working_days_in_month(2024, 6).unwrap(), 20);
+    }
+
+    #[test]
+    fn weekend_is_configurable() {
+        let calendar = BusinessCalendar::new(WeekendDays::new(&[Weekday::Fri, Weekday::Sat]));
+        // June 2024: 30 days, less 4 Fridays and 5 Saturdays
+        assert_eq!(calendar.working_days_in_month(2024, 6).unwrap(), 21);
+        assert!(calendar.is_business_day(date(2024, 6, 2)).unwrap()); // Sunday
+        assert!(!calendar.is_business_day(date(2024, 6, 7)).unwrap()); // Friday
+        assert_eq!(calendar.next_business_day(date(2024, 6, 6)).unwrap(), date(2024, 6, 9));
+        let holidays = HolidayCalendar::new(2024).unwrap().with_weekend(calendar.weekend);
+        assert!(holidays.is_business_day(date(2024, 6, 9)));
+    }
+
+    #[test]
+    fn business_days_of_january_2024() {
+        assert_eq!(first_business_day(2024, 1).unwrap(), date(2024, 1, 2));
+        assert_eq!(nth_business_day(2024, 1, 2).unwrap(), date(2024, 1, 3));
+        assert_eq!(last_business_day(2024, 1).unwrap(), date(2024, 1, 31));
+        assert!(nth_business_day(2024, 1, 22).is_err());
+        let closures = CustomHolidays::new().annual("年始休業", 1, 2).annual("年始休業", 1, 3);
+        let calendar = HolidayCalendar::with_custom(2024, &closures).unwrap();
+        assert_eq!(calendar.business_days(1).unwrap()[0], date(2024, 1, 4));
+    }
+
+    #[test]
+    fn weekend_is_threaded_through_every_rule() {
+        let calendar = BusinessCalendar::new(WeekendDays::new(&[Weekday::Fri, Weekday::Sat]));
+        assert_eq!(calendar.first_business_day(2024, 6).unwrap(), date(2024, 6, 2));
+        assert_eq!(calendar.last_business_day(2024, 5).unwrap(), date(2024, 5, 30));
+        assert_eq!(calendar.adjust(date(2024, 6, 7), RollRule::Following).unwrap(), date(2024, 6, 9));
+        // 2024-05-25 is a Saturday and the Friday before is a weekend day too
+        assert_eq!(calendar.paydays(2024, 25, RollRule::Preceding).unwrap()[4], date(2024, 5, 23));
+        let thursday = date(2024, 6, 6).and_hms_opt(10, 0, 0).unwrap();
+        assert_eq!(calendar.estimate(thursday, 1, 15).unwrap(), date(2024, 6, 9));
+        assert_eq!(estimate(thursday, 1, 15).unwrap(), date(2024, 6, 7));
+    }
+
+    // No month in 2020-2050 has the 25th on a Sunday before a Monday holiday, so the 7th stands in:
+    // 2024-01-07 is a Sunday and 2024-01-08 is 成人の日.
+    #[test]
+    fn payday_on_a_sunday_before_a_monday_holiday() {
+        assert_eq!(paydays(2024, 7, RollRule::Following).unwrap()[0], date(2024, 1, 9));
+        assert_eq!(paydays(2024, 7, RollRule::ModifiedFollowing).unwrap()[0], date(2024, 1, 9));
+        assert_eq!(paydays(2024, 7, RollRule::Preceding).unwrap()[0], date(2024, 1, 5));
+        // 2024-03-31 is a Sunday; the next business day is in April
+        assert_eq!(paydays(2024, 31, RollRule::ModifiedFollowing).unwrap()[2], date(2024, 3, 29));
+    }
+
+    #[test]
+    fn estimate_around_golden_week() {
+        // Friday 2024-04-26 after the cutoff: processed on 4/30 after 昭和の日, then 5/3-5/6 are off
+        let friday_night = date(2024, 4, 26).and_hms_opt(23, 0, 0).unwrap();
+        assert_eq!(estimate(friday_night, 0, 15).unwrap(), date(2024, 4, 30));
+        assert_eq!(estimate(friday_night, 3, 15).unwrap(), date(2024, 5, 7));
+        // a cutoff of 24 takes every order on a business day the same day
+        assert_eq!(estimate(friday_night, 0, 24).unwrap(), date(2024, 4, 26));
+    }
+
+    #[test]
+    fn cutoff_at_zero_always_waits() {
+        let monday_midnight = date(2024, 7, 1).and_hms_opt(0, 0, 0).unwrap();
+        assert_eq!(estimate(monday_midnight, 0, 0).unwrap(), date(2024, 7, 2));
+        assert_eq!(estimate(monday_midnight, 0, 1).unwrap(), date(2024, 7, 1));
+        assert!(estimate(monday_midnight, 0, 25).is_err());
+    }
+
+    #[test]
+    fn adjust_gives_up_without_business_days() {
+        let weekdays = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun];
+        let calendar = BusinessCalendar::new(WeekendDays::new(&weekdays));
+        assert!(calendar.adjust(date(2024, 6, 7), RollRule::Following).is_err());
+        assert!(calendar.adjust(date(2024, 6, 7), RollRule::ModifiedPreceding).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+//! # Zassetsu
+//! Seasonal markers (雑節) derived from the solar terms, e.g. 八十八夜 on 2024-05-01.
+//!
+//! - 節分: the day before 立春
+//! - 彼岸入り / 彼岸明け: three days before and after 春分 and 秋分
+//! - 八十八夜 and 二百十日: the 88th and 210th day counting 立春 as the first
+//! - 土用入り: the sun at 27°, 117°, 207° and 297°, starting the 18 days before each 立春, 立夏, 立秋 and 立冬
+//! - 土用の丑の日: the 丑 days (see `eto::day_eto`) of each 土用
+//!
+use anyhow::{anyhow, Result};
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+use super::eto::day_eto;
+use super::sekki::{solar_terms, sun_longitude_date};
+
+/// A seasonal marker
+#[derive(Serialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SeasonalDay {
+    pub date: NaiveDate, // date in Japan
+    pub name: String, // name of the marker (八十八夜)
+}
+
+// Longitude at which each 土用 starts, and the term that ends it.
+const DOYO: [(u32, &str); 4] = [(297, "立春"), (27, "立夏"), (117, "立秋"), (207, "立冬")];
+
+/// Get the seasonal markers (雑節) of a year, sorted by date.
+///
+/// Supports the same years as `sekki::solar_terms`.
+pub fn zassetsu(year: u32) -> Result<Vec<SeasonalDay>> {
+    let terms = solar_terms(year)?;
+    let term = |name: &str| -> Result<NaiveDate> {
+        terms.iter()
+            .find(|t| t.name == name)
+            .map(|t| t.date)
+            .ok_or_else(|| anyhow!("missing {} in {}", name, year))
+    };
+    let mut days = Vec::new();
+    let mut push = |name: &str, date: NaiveDate| days.push(SeasonalDay { date, name: name.to_string() });
+
+    let risshun = term("立春")?;
+    push("節分", risshun - Duration::days(1));
+    push("八十八夜", risshun + Duration::days(87));
+    push("二百十日", risshun + Duration::days(209));
+    for equinox in [term("春分")?, term("秋分")?] {
+        push("彼岸入り", equinox - Duration::days(3));
+        push("彼岸明け", equinox + Duration::days(3));
+    }
+    for (longitude, end) in DOYO {
+        let (start, end) = (sun_longitude_date(year, longitude), term(end)?);
+        push("土用入り", start);
+        for date in start.iter_days().take_while(|d| *d < end) {
+            if day_eto(date).branch() == "丑" {
+                push("土用の丑の日", date);
+            }
+        }
+    }
+    days.sort();
+    Ok(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+    use pretty_assertions::assert_eq;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn dates_of(year: u32, name: &str) -> Vec<NaiveDate> {
+        zassetsu(year).unwrap().into_iter().filter(|d| d.name == name).map(|d| d.date).collect()
+    }
+
+    #[test]
+    fn setsubun() {
+        assert_eq!(dates_of(2024, "節分"), [date(2024, 2, 3)]);
+        // 立春 on 2/3, as in 2021
+        assert_eq!(dates_of(2025, "節分"), [date(2025, 2, 2)]);
+        assert_eq!(dates_of(2021, "節分"), [date(2021, 2, 2)]);
+    }
+
+    #[test]
+    fn doyo() {
+        assert_eq!(dates_of(2024, "土用入り"), [date(2024, 1, 18), date(2024, 4, 16), date(2024, 7, 19), date(2024, 10, 20)]);
+        let summer = |year| dates_of(year, "土用の丑の日").into_iter().filter(|d| d.month() == 7 || d.month() == 8).collect::<Vec<_>>();
+        assert_eq!(summer(2024), [date(2024, 7, 24), date(2024, 8, 5)]);
+        assert_eq!(summer(2025), [date(2025, 7, 19), date(2025, 7, 31)]);
+    }
+}
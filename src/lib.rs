@@ -1,8 +1,18 @@
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsValue;
 use serde_wasm_bindgen::to_value;
-mod datebook;
-use datebook::calendar::holiday;
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Weekday};
+use serde::Serialize;
+pub mod datebook;
+pub mod prelude;
+use datebook::calendar::{self, compute_holidays, Holiday};
+use datebook::render::{format_holidays_with_options, OutputFormat, RenderOptions};
+use datebook::util::jp_format::waso_month_name;
+use datebook::util::parse_holiday_date;
+use datebook::timebase::all_years_with_equinox_data;
+use datebook::translation;
+use datebook::workday;
+use datebook::observance::{compute_holidays_with_observances, ObservanceSet, Prefecture};
 
 #[wasm_bindgen]
 extern "C" {
@@ -10,21 +20,535 @@ extern "C" {
     fn error(s: &str);
 }
 
+/// Compute the holidays of a year.
+///
+/// This is the main entry point for Rust callers; serialize the result with
+/// `datebook::render::format_holidays` when text output is needed.
+pub fn compute_holidays_raw(year: u32) -> anyhow::Result<Vec<Holiday>> {
+    compute_holidays(year)
+}
+
+/// Version of this crate, for diagnostics.
+pub const PACKAGE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Version of the module, e.g. "0.1.0".
+#[wasm_bindgen]
+pub fn version() -> String {
+    PACKAGE_VERSION.to_string()
+}
+
+/// First and last year of the equinox data, as `[2020, 2050]`; other years lack 春分の日 and 秋分の日.
+#[wasm_bindgen(unchecked_return_type = "[number, number]")]
+pub fn supported_year_range() -> js_sys::Array {
+    let years = all_years_with_equinox_data();
+    [years.first(), years.last()].into_iter()
+        .map(|year| year.map_or(JsValue::NULL, |y| JsValue::from(*y)))
+        .collect()
+}
+
 #[wasm_bindgen]
 pub fn holidays(year: i32) -> Result<JsValue, JsValue> {
-    match holiday(year as u32) {
+    match compute_holidays(year as u32) {
         Ok(holidays_data) => {
             match to_value(&holidays_data) {
                 Ok(js_value) =>  Ok(js_value),
-                Err(e) => {
-                    error(&format!("Failed to serialize to JSON: {:?}", e));
-                    Err(JsValue::NULL)
-                }
+                Err(e) => Err(js_error(&format!("Failed to serialize to JSON: {:#}", e))),
             }
         }
+        Err(e) => Err(js_error(&format!("Failed to get holidays: {:#}", e))),
+    }
+}
+
+#[wasm_bindgen]
+pub fn holidays_count(year: i32) -> u32 {
+    match compute_holidays(year as u32) {
+        Ok(holidays_data) => holidays_data.len() as u32,
         Err(e) => {
             error(&format!("Failed to get holidays: {:?}", e));
-            Err(JsValue::NULL)
+            0
         }
     }
 }
+
+#[wasm_bindgen]
+pub fn days_until_next_holiday(year: i32, month: u32, day: u32) -> Result<u32, JsValue> {
+    let from = to_date(year, month, day)?;
+    match calendar::days_until_next_holiday(from) {
+        Ok(days) => Ok(days),
+        Err(e) => Err(js_error(&format!("Failed to get next holiday: {:#}", e))),
+    }
+}
+
+/// `locale` ("en") translates the holiday names, see `datebook::translation`.
+#[wasm_bindgen]
+pub fn holidays_formatted(year: i32, format: &str, locale: Option<String>) -> Result<String, JsValue> {
+    let format = match format.parse::<OutputFormat>() {
+        Ok(f) => f,
+        Err(e) => return Err(js_error(&e.to_string())),
+    };
+    let options = RenderOptions { locale, ..RenderOptions::default() };
+    match compute_holidays(year as u32).and_then(|h| format_holidays_with_options(&h, format, &options)) {
+        Ok(s) => Ok(s),
+        Err(e) => Err(js_error(&format!("Failed to get holidays: {:#}", e))),
+    }
+}
+
+#[wasm_bindgen(js_name = "isHoliday")]
+pub fn is_holiday_wasm(year: i32, month: u32, day: u32) -> Result<bool, JsValue> {
+    let date = to_date(year, month, day)?;
+    match calendar::is_holiday(date) {
+        Ok(v) => Ok(v),
+        Err(e) => Err(js_error(&format!("Failed to get holidays: {:#}", e))),
+    }
+}
+
+/// Name of the holiday on a date, or `null`.
+#[wasm_bindgen(unchecked_return_type = "string | null")]
+pub fn get_holiday_on(year: i32, month: u32, day: u32) -> Result<JsValue, JsValue> {
+    let date = to_date(year, month, day)?;
+    match calendar::holiday_on(date) {
+        Ok(Some(h)) => Ok(JsValue::from_str(&h.name)),
+        Ok(None) => Ok(JsValue::NULL),
+        Err(e) => Err(js_error(&format!("Failed to get holidays: {:#}", e))),
+    }
+}
+
+#[wasm_bindgen(js_name = "isBusinessDay")]
+pub fn is_business_day_wasm(year: i32, month: u32, day: u32) -> Result<bool, JsValue> {
+    let date = to_date(year, month, day)?;
+    match calendar::is_business_day(date) {
+        Ok(v) => Ok(v),
+        Err(e) => Err(js_error(&format!("Failed to get holidays: {:#}", e))),
+    }
+}
+
+#[cfg(feature = "msgpack")]
+#[wasm_bindgen]
+pub fn holidays_msgpack(year: i32) -> Result<js_sys::Uint8Array, JsValue> {
+    let rendered = compute_holidays(year as u32)
+        .and_then(|h| datebook::render::render_msgpack(&h));
+    match rendered {
+        Ok(bytes) => Ok(js_sys::Uint8Array::from(bytes.as_slice())),
+        Err(e) => Err(js_error(&format!("Failed to serialize to MessagePack: {:#}", e))),
+    }
+}
+
+#[wasm_bindgen]
+pub fn next_business_day_after(year: i32, month: u32, day: u32) -> Result<JsValue, JsValue> {
+    let date = to_date(year, month, day)?;
+    match calendar::next_business_day(date) {
+        Ok(next) => to_js(&YearMonthDay::from(next)),
+        Err(e) => Err(js_error(&format!("Failed to get next business day: {:#}", e))),
+    }
+}
+
+#[wasm_bindgen]
+pub fn holiday_stats(year: i32) -> Result<JsValue, JsValue> {
+    match calendar::stats(year as u32) {
+        Ok(stats) => to_js(&stats),
+        Err(e) => Err(js_error(&format!("Failed to get holiday stats: {:#}", e))),
+    }
+}
+
+#[wasm_bindgen]
+pub fn bridge_days(year: i32, max_leave: u32) -> Result<JsValue, JsValue> {
+    match calendar::bridge_days(year as u32, max_leave) {
+        Ok(suggestions) => to_js(&suggestions),
+        Err(e) => Err(js_error(&format!("Failed to get bridge days: {:#}", e))),
+    }
+}
+
+#[wasm_bindgen]
+pub fn working_days_in_month(year: i32, month: u32) -> Result<u32, JsValue> {
+    match workday::working_days_in_month(year as u32, month) {
+        Ok(days) => Ok(days),
+        Err(e) => Err(js_error(&format!("Failed to count working days: {:#}", e))),
+    }
+}
+
+#[wasm_bindgen]
+pub fn working_days_in_year(year: i32) -> Result<u32, JsValue> {
+    match workday::working_days_in_year(year as u32) {
+        Ok(days) => Ok(days),
+        Err(e) => Err(js_error(&format!("Failed to count working days: {:#}", e))),
+    }
+}
+
+#[wasm_bindgen]
+pub fn first_business_day(year: i32, month: u32) -> Result<JsValue, JsValue> {
+    business_day_result(workday::first_business_day(year as u32, month))
+}
+
+#[wasm_bindgen]
+pub fn last_business_day(year: i32, month: u32) -> Result<JsValue, JsValue> {
+    business_day_result(workday::last_business_day(year as u32, month))
+}
+
+/// `n` counts from 1.
+#[wasm_bindgen]
+pub fn nth_business_day(year: i32, month: u32, n: u32) -> Result<JsValue, JsValue> {
+    business_day_result(workday::nth_business_day(year as u32, month, n))
+}
+
+fn business_day_result(result: anyhow::Result<NaiveDate>) -> Result<JsValue, JsValue> {
+    match result {
+        Ok(date) => to_js(&YearMonthDay::from(date)),
+        Err(e) => Err(js_error(&format!("Failed to get business day: {:#}", e))),
+    }
+}
+
+/// Move a date to a business day by `rule` ("preceding", "following",
+/// "modified_following" or "modified_preceding").
+#[wasm_bindgen]
+pub fn adjust_to_business_day(year: i32, month: u32, day: u32, rule: &str) -> Result<JsValue, JsValue> {
+    let date = to_date(year, month, day)?;
+    let rule = parse_roll_rule(rule)?;
+    match workday::adjust(date, rule) {
+        Ok(adjusted) => to_js(&YearMonthDay::from(adjusted)),
+        Err(e) => Err(js_error(&format!("Failed to adjust date: {:#}", e))),
+    }
+}
+
+/// Paydays of a year on `day_of_month`, moved by `rule` as in `adjust_to_business_day`.
+#[wasm_bindgen]
+pub fn paydays(year: i32, day_of_month: u32, rule: &str) -> Result<JsValue, JsValue> {
+    let rule = parse_roll_rule(rule)?;
+    match workday::paydays(year as u32, day_of_month, rule) {
+        Ok(days) => to_js(&days.into_iter().map(YearMonthDay::from).collect::<Vec<_>>()),
+        Err(e) => Err(js_error(&format!("Failed to get paydays: {:#}", e))),
+    }
+}
+
+/// Delivery date of an order placed at `ordered_at` (`YYYY-MM-DDTHH:MM[:SS]`), as in
+/// `workday::estimate`.
+#[wasm_bindgen]
+pub fn estimate_delivery(ordered_at: &str, business_days: u32, cutoff_hour: u32) -> Result<JsValue, JsValue> {
+    let ordered_at = match NaiveDateTime::parse_from_str(ordered_at, "%Y-%m-%dT%H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(ordered_at, "%Y-%m-%dT%H:%M"))
+    {
+        Ok(dt) => dt,
+        Err(e) => return Err(js_error(&format!("Invalid date and time '{}': {}", ordered_at, e))),
+    };
+    match workday::estimate(ordered_at, business_days, cutoff_hour) {
+        Ok(date) => to_js(&YearMonthDay::from(date)),
+        Err(e) => Err(js_error(&format!("Failed to estimate delivery: {:#}", e))),
+    }
+}
+
+/// The next `count` holidays from today in Japan, today included.
+#[wasm_bindgen]
+pub fn upcoming_holidays(count: u32) -> Result<JsValue, JsValue> {
+    match calendar::upcoming_holidays(today_jst()?, count as usize) {
+        Ok(holidays_data) => to_js(&holidays_data),
+        Err(e) => Err(js_error(&format!("Failed to get holidays: {:#}", e))),
+    }
+}
+
+/// Holidays of the current week in Japan, Monday to Sunday, or Sunday to Saturday
+/// when `sunday_start` is set.
+#[wasm_bindgen]
+pub fn holidays_this_week(sunday_start: Option<bool>) -> Result<JsValue, JsValue> {
+    let week_start = if sunday_start.unwrap_or(false) { Weekday::Sun } else { Weekday::Mon };
+    match calendar::holidays_in_week(today_jst()?, week_start) {
+        Ok(holidays_data) => to_js(&holidays_data),
+        Err(e) => Err(js_error(&format!("Failed to get holidays: {:#}", e))),
+    }
+}
+
+/// Week rows of a month for a month view, Monday to Sunday, or Sunday to Saturday when
+/// `sunday_start` is set. Each cell is `{ date, in_month, weekday, holiday, kind }`.
+#[wasm_bindgen]
+pub fn month_grid(year: i32, month: u32, sunday_start: Option<bool>) -> Result<JsValue, JsValue> {
+    let week_start = if sunday_start.unwrap_or(false) { Weekday::Sun } else { Weekday::Mon };
+    match calendar::month_grid(year as u32, month, week_start) {
+        Ok(grid) => to_js(&grid.weeks),
+        Err(e) => Err(js_error(&format!("Failed to get holidays: {:#}", e))),
+    }
+}
+
+/// Holidays of the current month in Japan.
+#[wasm_bindgen]
+pub fn holidays_this_month() -> Result<JsValue, JsValue> {
+    let today = today_jst()?;
+    match calendar::holidays_in_month(today.year() as u32, today.month()) {
+        Ok(holidays_data) => to_js(&holidays_data),
+        Err(e) => Err(js_error(&format!("Failed to get holidays: {:#}", e))),
+    }
+}
+
+/// Whether today in Japan is a holiday.
+#[wasm_bindgen]
+pub fn is_today_holiday() -> Result<bool, JsValue> {
+    match calendar::is_holiday(today_jst()?) {
+        Ok(v) => Ok(v),
+        Err(e) => Err(js_error(&format!("Failed to get holidays: {:#}", e))),
+    }
+}
+
+#[wasm_bindgen]
+pub fn holidays_fiscal_year(fy: i32) -> Result<JsValue, JsValue> {
+    match calendar::holidays_in_fiscal_year(fy as u32) {
+        Ok(holidays_data) => to_js(&holidays_data),
+        Err(e) => Err(js_error(&format!("Failed to get holidays: {:#}", e))),
+    }
+}
+
+/// `start` and `end` are `YYYY-MM-DD` (or `YYYY/MM/DD`) strings, both inclusive.
+#[wasm_bindgen]
+pub fn has_holiday_between(start: &str, end: &str) -> Result<bool, JsValue> {
+    let (start, end) = (parse_date(start)?, parse_date(end)?);
+    match calendar::has_holiday_between(start, end) {
+        Ok(v) => Ok(v),
+        Err(e) => Err(js_error(&format!("Failed to get holidays: {:#}", e))),
+    }
+}
+
+/// `config` is a JSON `HolidayConfig`, e.g.
+/// `{"extra_holidays": [["2024-06-03", "創立記念日"]], "excluded_names": ["山の日"], "include_observances": true}`.
+#[wasm_bindgen]
+pub fn holidays_with_config(year: i32, config: &str) -> Result<JsValue, JsValue> {
+    let config: calendar::HolidayConfig = match serde_json::from_str(config) {
+        Ok(c) => c,
+        Err(e) => return Err(js_error(&format!("Invalid holiday config: {}", e))),
+    };
+    match calendar::compute_holidays_with_config(year as u32, &config) {
+        Ok(holidays_data) => to_js(&holidays_data),
+        Err(e) => Err(js_error(&format!("Failed to get holidays: {:#}", e))),
+    }
+}
+
+/// Holidays of a year, plus the observances of `region` ("okinawa", "沖縄県") when given.
+#[wasm_bindgen]
+pub fn holidays_with_region(year: i32, region: Option<String>) -> Result<JsValue, JsValue> {
+    let sets = match region.as_deref().map(str::parse::<Prefecture>) {
+        None => Vec::new(),
+        Some(Ok(prefecture)) => vec![ObservanceSet::Regional(prefecture)],
+        Some(Err(e)) => return Err(js_error(&e.to_string())),
+    };
+    match compute_holidays_with_observances(year as u32, &sets) {
+        Ok(holidays_data) => to_js(&holidays_data),
+        Err(e) => Err(js_error(&format!("Failed to get holidays: {:#}", e))),
+    }
+}
+
+/// The 24 solar terms (二十四節気) of a year as `{ name, date }` objects.
+#[wasm_bindgen]
+pub fn solar_terms(year: i32) -> Result<JsValue, JsValue> {
+    match datebook::sekki::solar_terms(year as u32) {
+        Ok(terms) => to_js(&terms),
+        Err(e) => Err(js_error(&format!("Failed to get solar terms: {:#}", e))),
+    }
+}
+
+/// Japanese era of a date as `{ era: "令和", year: 6 }`, or `null` before the Meiji era
+/// and for invalid dates.
+#[wasm_bindgen]
+pub fn get_japanese_era(year: i32, month: u32, day: u32) -> Result<JsValue, JsValue> {
+    let Some(date) = NaiveDate::from_ymd_opt(year, month, day) else {
+        return Ok(JsValue::NULL);
+    };
+    match datebook::era::era_of(date) {
+        Some((era, era_year)) => to_js(&EraYear { era: era.kanji(), year: era_year }),
+        None => Ok(JsValue::NULL),
+    }
+}
+
+/// 六曜 of a date in kanji ("大安").
+#[wasm_bindgen]
+pub fn rokuyo(year: i32, month: u32, day: u32) -> Result<String, JsValue> {
+    let date = to_date(year, month, day)?;
+    match datebook::rokuyo::rokuyo_for(date) {
+        Ok(r) => Ok(r.kanji().to_string()),
+        Err(e) => Err(js_error(&format!("Failed to get rokuyo: {:#}", e))),
+    }
+}
+
+/// 干支 of a year as `{ kanji, stem, branch, animal }`, e.g. 甲辰 (竜) for 2024.
+#[wasm_bindgen]
+pub fn year_eto(year: i32) -> Result<JsValue, JsValue> {
+    to_js(&datebook::eto::year_eto(year))
+}
+
+/// Traditional name of a month (和風月名), e.g. "睦月" for 1.
+#[wasm_bindgen]
+pub fn month_name_traditional(month: u32) -> Result<String, JsValue> {
+    match waso_month_name(month) {
+        Some(name) => Ok(name.to_string()),
+        None => Err(js_error(&format!("Invalid month: {}", month))),
+    }
+}
+
+/// Seasonal markers (雑節) of a year as `{ date, name }` objects.
+#[wasm_bindgen]
+pub fn zassetsu(year: i32) -> Result<JsValue, JsValue> {
+    match datebook::zassetsu::zassetsu(year as u32) {
+        Ok(days) => to_js(&days),
+        Err(e) => Err(js_error(&format!("Failed to get seasonal markers: {:#}", e))),
+    }
+}
+
+/// JSON Schema of the objects returned by `holidays`.
+#[wasm_bindgen]
+pub fn holiday_json_schema() -> String {
+    datebook::render::holiday_json_schema()
+}
+
+/// Holidays of a year with names in `locale`, as `holidays` plus a `translated` flag
+/// that is `false` where the Japanese name is kept.
+#[wasm_bindgen]
+pub fn holidays_localized(year: i32, locale: &str) -> Result<JsValue, JsValue> {
+    match compute_holidays(year as u32) {
+        Ok(holidays_data) => {
+            let localized: Vec<LocalizedHoliday> = holidays_data.into_iter()
+                .map(|holiday| {
+                    let translation = translation::translate(&holiday, locale);
+                    LocalizedHoliday { holiday: Holiday { name: translation.name, ..holiday }, translated: translation.translated }
+                })
+                .collect();
+            to_js(&localized)
+        }
+        Err(e) => Err(js_error(&format!("Failed to get holidays: {:#}", e))),
+    }
+}
+
+/// Register the name of the holiday `id` ("sports-day") in `locale`.
+#[wasm_bindgen]
+pub fn register_translation(locale: &str, id: &str, name: &str) {
+    translation::register(locale, id, name);
+}
+
+/// A holiday as a JS class with typed getters.
+#[wasm_bindgen(js_name = "Holiday")]
+pub struct WasmHoliday {
+    inner: Holiday,
+}
+
+#[wasm_bindgen(js_class = "Holiday")]
+impl WasmHoliday {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.inner.name.clone()
+    }
+
+    /// `YYYY-MM-DD`
+    #[wasm_bindgen(getter)]
+    pub fn date_string(&self) -> String {
+        self.inner.date.to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn substitute(&self) -> bool {
+        self.inner.substitute
+    }
+}
+
+impl From<Holiday> for WasmHoliday {
+    fn from(inner: Holiday) -> Self {
+        WasmHoliday { inner }
+    }
+}
+
+/// Every day of a year from January 1 as 0 (workday), 1 (weekend) or 2 (holiday),
+/// with 366 entries in a leap year. A holiday on a weekend is 2.
+#[wasm_bindgen]
+pub fn day_classification(year: i32) -> Result<js_sys::Uint8Array, JsValue> {
+    let first = match NaiveDate::from_ymd_opt(year, 1, 1) {
+        Some(date) => date,
+        None => return Err(js_error(&format!("Invalid year: {}", year))),
+    };
+    match calendar::HolidaySet::new(year as u32) {
+        Ok(set) => {
+            let days: Vec<u8> = first.iter_days()
+                .take_while(|d| d.year() == year)
+                .map(|d| if set.contains(d) {
+                    2
+                } else if matches!(d.weekday(), Weekday::Sat | Weekday::Sun) {
+                    1
+                } else {
+                    0
+                })
+                .collect();
+            Ok(js_sys::Uint8Array::from(days.as_slice()))
+        }
+        Err(e) => Err(js_error(&format!("Failed to get holidays: {:#}", e))),
+    }
+}
+
+/// Like `holidays`, as an array of `Holiday` class instances instead of plain objects.
+#[wasm_bindgen]
+pub fn holiday_list(year: i32) -> Result<js_sys::Array, JsValue> {
+    match compute_holidays(year as u32) {
+        Ok(holidays_data) => Ok(holidays_data.into_iter().map(WasmHoliday::from).map(JsValue::from).collect()),
+        Err(e) => Err(js_error(&format!("Failed to get holidays: {:#}", e))),
+    }
+}
+
+// A holiday with a translated name for JS
+#[derive(Serialize)]
+struct LocalizedHoliday {
+    #[serde(flatten)]
+    holiday: Holiday,
+    translated: bool,
+}
+
+// { era, year } object for JS
+#[derive(Serialize)]
+struct EraYear {
+    era: &'static str,
+    year: u32,
+}
+
+// { year, month, day } object for JS
+#[derive(Serialize)]
+struct YearMonthDay {
+    year: i32,
+    month: u32,
+    day: u32,
+}
+
+impl From<NaiveDate> for YearMonthDay {
+    fn from(date: NaiveDate) -> Self {
+        YearMonthDay { year: date.year(), month: date.month(), day: date.day() }
+    }
+}
+
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    match to_value(value) {
+        Ok(js_value) => Ok(js_value),
+        Err(e) => Err(js_error(&format!("Failed to serialize to JSON: {:#}", e))),
+    }
+}
+
+// Today in JST from the JS clock; the host time zone is ignored.
+fn today_jst() -> Result<NaiveDate, JsValue> {
+    match DateTime::from_timestamp_millis(js_sys::Date::now() as i64) {
+        Some(now) => Ok(calendar::jst_date(now)),
+        None => Err(js_error("Invalid current time")),
+    }
+}
+
+// An `Error` with `message` for JS callers, thrown by the export or rejecting its promise.
+fn js_error(message: &str) -> JsValue {
+    JsError::new(message).into()
+}
+
+fn to_date(year: i32, month: u32, day: u32) -> Result<NaiveDate, JsValue> {
+    match NaiveDate::from_ymd_opt(year, month, day) {
+        Some(date) => Ok(date),
+        None => Err(js_error(&format!("Invalid date: {}-{}-{}", year, month, day))),
+    }
+}
+
+fn parse_roll_rule(s: &str) -> Result<workday::RollRule, JsValue> {
+    match s.parse() {
+        Ok(rule) => Ok(rule),
+        Err(e) => Err(js_error(&e.to_string())),
+    }
+}
+
+fn parse_date(s: &str) -> Result<NaiveDate, JsValue> {
+    match parse_holiday_date(s, None) {
+        Ok(date) => Ok(date),
+        Err(e) => Err(js_error(&e.to_string())),
+    }
+}
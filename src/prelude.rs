@@ -0,0 +1,7 @@
+//! # Prelude
+//! The types and functions most programs need: `use jpn_holidays_wasm::prelude::*;`
+//!
+pub use crate::datebook::calendar::{
+    compute_holidays, is_business_day, is_holiday, CustomHolidays, Holiday, HolidayCalendar, HolidayConfig, HolidayRef, HolidaySet, HolidayType,
+};
+pub use crate::datebook::render::{format_holidays, format_holidays_with_options, OutputFormat, RenderOptions};